@@ -1,13 +1,28 @@
 mod logger;
 
-pub use logger::{appender::*, logger::*, writer::*};
+#[cfg(feature = "config")]
+pub use logger::config::*;
+pub use logger::{
+    appender::*, async_writer::*, builder::*, clock::*, error::*, filter::*, formatter::*, global::*, handle::*,
+    json::*, logfmt::*, logger::*, pattern::*, retention::*, tee::*, theme::*, timestamp::*, writer::*,
+};
 
 /// Default Logger, will output to stderr
 pub type Logger = BaseLogger<NopAppender>;
 /// Logger that outputs to stdout
 pub type StdoutLogger = BaseLogger<NopAppender, Stdout>;
+/// Logger that outputs warnings and errors to stderr and the rest to stdout
+pub type SplitLogger = BaseLogger<NopAppender, SplitWriter>;
 /// Logger that outputs to a file
 pub type FileLogger = BaseLogger<NopAppender, LogFileWriter>;
+/// Logger that outputs to a file rotated by size
+pub type RotatingFileLogger = BaseLogger<NopAppender, RotatingFileWriter>;
+/// Logger that outputs to a file rotated daily or hourly
+pub type TimeRotatingFileLogger = BaseLogger<NopAppender, TimeRotatingFileWriter>;
+/// Logger that outputs to a file which can be reopened after external rotation
+pub type ReopenableFileLogger = BaseLogger<NopAppender, ReopenableFileWriter>;
+/// Logger configured from environment variables, see [`BaseLogger::init_from_env`]
+pub type EnvLogger = BaseLogger<NopAppender, Target>;
 
 /// log_print! can be used before the logging framework is initialized
 ///
@@ -25,10 +40,59 @@ pub type FileLogger = BaseLogger<NopAppender, LogFileWriter>;
 #[macro_export]
 macro_rules! log_print {
     ($level:path, $($arg:tt)*) => {
-        $crate::Logger::print($level, module_path!(), &format!($($arg)*));
+        $crate::Logger::print_at($level, module_path!(), file!(), line!(), &format!($($arg)*));
     };
 }
 
+/// Tests that install a logger hold this lock, so they don't replace each other's logger mid-test
+#[cfg(test)]
+static GLOBAL_LOGGER_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+/// In-memory writer for tests that check the formatted output
+#[cfg(test)]
+#[derive(Clone, Default)]
+struct MemoryWriter(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+
+#[cfg(test)]
+impl MemoryWriter {
+    fn contents(&self) -> String {
+        String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+    }
+}
+
+#[cfg(test)]
+impl std::io::Write for MemoryWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+impl LogWriter for MemoryWriter {
+    type Stream = Self;
+
+    fn get(&self) -> Self::Stream {
+        self.clone()
+    }
+}
+
+/// Context with a fixed `T` timestamp and no extra or colors, for tests that format records directly
+#[cfg(test)]
+const TEST_CONTEXT: FormatContext<'static> =
+    FormatContext { timestamp: std::time::Duration::ZERO, time: Some("T"), utc_offset: 0, extra: None, theme: None };
+
+/// Format one record into a string
+#[cfg(test)]
+fn format_line(formatter: &dyn LogFormatter, record: &log::Record, ctx: &FormatContext) -> String {
+    let mut line = Vec::new();
+    formatter.format(&mut line, record, ctx).unwrap();
+    String::from_utf8(line).unwrap()
+}
+
 #[test]
 fn test_log_appender() {
     use std::io::Write;
@@ -45,6 +109,7 @@ fn test_log_appender() {
         }
     }
 
+    let _lock = GLOBAL_LOGGER_LOCK.lock().unwrap();
     type MyLogger = BaseLogger<PIDLogAppender>;
     MyLogger::init(LevelFilter::Debug);
 
@@ -71,3 +136,1018 @@ fn test_log_file_writer() {
     assert!(String::from_utf8(fs::read("test_log.txt").unwrap()).unwrap().contains("test log message"));
     fs::remove_file("test_log.txt").unwrap();
 }
+
+#[test]
+fn test_rotating_file_writer() {
+    use std::{fs, io::Write};
+
+    let dir = std::env::temp_dir().join(format!("rs_logger_rotating_{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("app.log");
+
+    let writer = RotatingFileWriter::new(&path, 16, 2).unwrap();
+    for i in 0..4 {
+        writer.get().write_all(format!("line {i} 0123\n").as_bytes()).unwrap();
+    }
+
+    assert_eq!(fs::read_to_string(&path).unwrap(), "line 3 0123\n");
+    assert_eq!(fs::read_to_string(dir.join("app.log.1")).unwrap(), "line 2 0123\n");
+    assert_eq!(fs::read_to_string(dir.join("app.log.2")).unwrap(), "line 1 0123\n");
+    assert!(!dir.join("app.log.3").exists());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_rotating_file_writer_failed_rotation() {
+    use std::{fs, io::Write};
+
+    let dir = std::env::temp_dir().join(format!("rs_logger_failed_rotation_{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("app.log");
+    // a non-empty directory where the backup goes makes the rename fail
+    fs::create_dir_all(dir.join("app.log.1")).unwrap();
+    fs::write(dir.join("app.log.1").join("keep"), "").unwrap();
+
+    let writer = RotatingFileWriter::new(&path, 16, 1).unwrap();
+    writer.get().write_all(b"line 0 0123\n").unwrap();
+    writer.get().write_all(b"line 1 0123\n").unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "line 0 0123\nline 1 0123\n");
+
+    fs::remove_dir_all(dir.join("app.log.1")).unwrap();
+    writer.get().write_all(b"line 2 0123\n").unwrap();
+    assert_eq!(fs::read_to_string(dir.join("app.log.1")).unwrap(), "line 0 0123\nline 1 0123\n");
+    assert_eq!(fs::read_to_string(&path).unwrap(), "line 2 0123\n");
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_time_rotating_file_writer() {
+    use std::{fs, io::Write, time::Duration};
+
+    let dir = std::env::temp_dir().join(format!("rs_logger_time_rotating_{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+
+    // 2026-10-17T23:59:59Z
+    let clock = ManualClock::new(Duration::from_secs(1792281599));
+    let writer = TimeRotatingFileWriter::open_with_clock(
+        dir.join("app.log"),
+        Rotation::Daily,
+        &FileOptions::new(),
+        clock.clone(),
+    )
+    .unwrap();
+    writer.get().write_all(b"before midnight\n").unwrap();
+    clock.advance(Duration::from_secs(1));
+    assert!(!dir.join("app.2026-10-18.log").exists());
+    writer.get().write_all(b"after midnight\n").unwrap();
+    assert_eq!(fs::read_to_string(dir.join("app.2026-10-17.log")).unwrap(), "before midnight\n");
+    assert_eq!(fs::read_to_string(dir.join("app.2026-10-18.log")).unwrap(), "after midnight\n");
+
+    let writer =
+        TimeRotatingFileWriter::open_with_clock(dir.join("app"), Rotation::Hourly, &FileOptions::new(), clock.clone())
+            .unwrap();
+    writer.get().write_all(b"first hour\n").unwrap();
+    clock.advance(Duration::from_secs(3599));
+    writer.get().write_all(b"still first hour\n").unwrap();
+    clock.advance(Duration::from_secs(1));
+    writer.get().write_all(b"second hour\n").unwrap();
+    assert_eq!(fs::read_to_string(dir.join("app.2026-10-18-00")).unwrap(), "first hour\nstill first hour\n");
+    assert_eq!(fs::read_to_string(dir.join("app.2026-10-18-01")).unwrap(), "second hour\n");
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_time_rotating_file_writer_failed_rotation() {
+    use std::{fs, io::Write, time::Duration};
+
+    let dir = std::env::temp_dir().join(format!("rs_logger_failed_time_rotation_{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    // a directory where the next dated file goes makes the rollover fail
+    fs::create_dir_all(dir.join("app.2026-10-18.log")).unwrap();
+
+    // 2026-10-17T23:59:59Z
+    let clock = ManualClock::new(Duration::from_secs(1792281599));
+    let writer = TimeRotatingFileWriter::open_with_clock(
+        dir.join("app.log"),
+        Rotation::Daily,
+        &FileOptions::new(),
+        clock.clone(),
+    )
+    .unwrap();
+    writer.get().write_all(b"before midnight\n").unwrap();
+    clock.advance(Duration::from_secs(1));
+    writer.get().write_all(b"after midnight\n").unwrap();
+    assert_eq!(fs::read_to_string(dir.join("app.2026-10-17.log")).unwrap(), "before midnight\nafter midnight\n");
+
+    fs::remove_dir(dir.join("app.2026-10-18.log")).unwrap();
+    writer.get().write_all(b"retried\n").unwrap();
+    assert_eq!(fs::read_to_string(dir.join("app.2026-10-18.log")).unwrap(), "retried\n");
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_rotating_file_writer_retention() {
+    use std::{fs, io::Write};
+
+    let dir = std::env::temp_dir().join(format!("rs_logger_retention_{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("app.log");
+
+    let writer = RotatingFileWriter::new(&path, 16, 5).unwrap().with_retention(Retention::new().max_total_size(24));
+    for i in 0..5 {
+        writer.get().write_all(format!("line {i} 0123\n").as_bytes()).unwrap();
+    }
+    // dropping the writer waits for the last retention run
+    drop(writer);
+
+    assert!(dir.join("app.log.1").exists());
+    assert!(dir.join("app.log.2").exists());
+    assert!(!dir.join("app.log.3").exists());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_time_rotating_file_writer_retention() {
+    use std::{fs, io::Write, time::Duration};
+
+    let dir = std::env::temp_dir().join(format!("rs_logger_time_retention_{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    for name in ["app.2026-10-16.log", "app.error.log", "app.error.2026-10-16.log", "app.2026-10-16.txt"] {
+        fs::write(dir.join(name), "old\n").unwrap();
+    }
+
+    // 2026-10-17T23:59:59Z
+    let clock = ManualClock::new(Duration::from_secs(1792281599));
+    let writer = TimeRotatingFileWriter::open_with_clock(
+        dir.join("app.log"),
+        Rotation::Daily,
+        &FileOptions::new(),
+        clock.clone(),
+    )
+    .unwrap()
+    .with_retention(Retention::new().max_total_size(0));
+    writer.get().write_all(b"before midnight\n").unwrap();
+    clock.advance(Duration::from_secs(1));
+    writer.get().write_all(b"after midnight\n").unwrap();
+    drop(writer);
+
+    // only this writer's dated files are deleted
+    assert!(!dir.join("app.2026-10-16.log").exists());
+    assert!(!dir.join("app.2026-10-17.log").exists());
+    assert!(dir.join("app.2026-10-18.log").exists());
+    assert!(dir.join("app.error.log").exists());
+    assert!(dir.join("app.error.2026-10-16.log").exists());
+    assert!(dir.join("app.2026-10-16.txt").exists());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(feature = "gzip")]
+#[test]
+fn test_rotating_file_writer_compression() {
+    use std::{fs, io::Write};
+
+    let dir = std::env::temp_dir().join(format!("rs_logger_compression_{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("app.log");
+
+    let writer = RotatingFileWriter::new(&path, 16, 2).unwrap().with_retention(Retention::new().compress(true));
+    for i in 0..3 {
+        writer.get().write_all(format!("line {i} 0123\n").as_bytes()).unwrap();
+    }
+    drop(writer);
+
+    assert!(dir.join("app.log.1.gz").exists());
+    assert!(dir.join("app.log.2.gz").exists());
+    assert!(!dir.join("app.log.1").exists());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_reopenable_file_writer() {
+    use std::{fs, io::Write};
+
+    let dir = std::env::temp_dir().join(format!("rs_logger_reopenable_{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("app.log");
+
+    let writer = ReopenableFileWriter::new(&path).unwrap();
+    writer.get().write_all(b"before rotation\n").unwrap();
+    fs::rename(&path, dir.join("app.log.1")).unwrap();
+    writer.get().write_all(b"still the moved file\n").unwrap();
+    writer.handle().reopen().unwrap();
+    writer.get().write_all(b"after reopen\n").unwrap();
+
+    assert_eq!(fs::read_to_string(dir.join("app.log.1")).unwrap(), "before rotation\nstill the moved file\n");
+    assert_eq!(fs::read_to_string(&path).unwrap(), "after reopen\n");
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(all(unix, feature = "signal"))]
+#[test]
+fn test_reopenable_file_writer_sighup() {
+    use std::{fs, io::Write};
+
+    let dir = std::env::temp_dir().join(format!("rs_logger_sighup_{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("app.log");
+
+    let writer = ReopenableFileWriter::new(&path).unwrap().reopen_on_sighup().unwrap();
+    writer.get().write_all(b"before rotation\n").unwrap();
+    fs::rename(&path, dir.join("app.log.1")).unwrap();
+    // a directory at the path makes the reopen fail, the record stays in the moved file
+    fs::create_dir(&path).unwrap();
+    signal_hook::low_level::raise(signal_hook::consts::SIGHUP).unwrap();
+    writer.get().write_all(b"failed reopen\n").unwrap();
+    assert_eq!(fs::read_to_string(dir.join("app.log.1")).unwrap(), "before rotation\nfailed reopen\n");
+
+    // the reopen is retried on the next write
+    fs::remove_dir(&path).unwrap();
+    writer.get().write_all(b"after reopen\n").unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "after reopen\n");
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_log_file_writer_open() {
+    use std::{fs, io::Write};
+
+    let dir = std::env::temp_dir().join(format!("rs_logger_open_{}", std::process::id()));
+    let path = dir.join("nested").join("app.log");
+
+    let options = FileOptions::new();
+    #[cfg(unix)]
+    let options = options.mode(0o600);
+    LogFileWriter::open(&path, &options).unwrap().get().write_all(b"first\n").unwrap();
+    LogFileWriter::open(&path, &options).unwrap().get().write_all(b"second\n").unwrap();
+
+    assert_eq!(fs::read_to_string(&path).unwrap(), "first\nsecond\n");
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+    }
+    assert!(LogFileWriter::open(dir.join("nested"), &options).is_err());
+
+    // the other path based writers create their directory and honour the mode too
+    RotatingFileWriter::open(dir.join("rotating").join("app.log"), 1024, 1, &options).unwrap();
+    TimeRotatingFileWriter::open(dir.join("time").join("app"), Rotation::Daily, &options).unwrap();
+    ReopenableFileWriter::open(dir.join("reopenable").join("app.log"), &options).unwrap();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = |path: std::path::PathBuf| fs::metadata(path).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode(dir.join("rotating").join("app.log")), 0o600);
+        assert_eq!(mode(dir.join("reopenable").join("app.log")), 0o600);
+        let dated = fs::read_dir(dir.join("time")).unwrap().next().unwrap().unwrap().path();
+        assert_eq!(mode(dated), 0o600);
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_try_init() {
+    use log::LevelFilter;
+
+    let _lock = GLOBAL_LOGGER_LOCK.lock().unwrap();
+    Logger::init(LevelFilter::Debug);
+    assert!(matches!(StdoutLogger::try_init(LevelFilter::Info), Err(InitError::AlreadyInitialized)));
+}
+
+#[test]
+fn test_global_logger_replace() {
+    use std::sync::{Arc, Mutex};
+
+    use log::{LevelFilter, Log, Metadata, Record};
+
+    #[derive(Clone, Default)]
+    struct CaptureLogger(Arc<Mutex<Vec<String>>>);
+
+    impl Log for CaptureLogger {
+        fn enabled(&self, _metadata: &Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, record: &Record) {
+            self.0.lock().unwrap().push(format!("{} {}", record.level(), record.args()));
+        }
+
+        fn flush(&self) {}
+    }
+
+    let _lock = GLOBAL_LOGGER_LOCK.lock().unwrap();
+    Logger::init(LevelFilter::Info);
+    StdoutLogger::init(LevelFilter::Debug);
+    assert_eq!(log::max_level(), LevelFilter::Debug);
+
+    let first = CaptureLogger::default();
+    let second = CaptureLogger::default();
+    GlobalLogger::replace(first.clone(), LevelFilter::Info).unwrap();
+    log::info!("to first");
+    log::debug!("filtered");
+    GlobalLogger::replace(second.clone(), LevelFilter::Trace).unwrap();
+    log::debug!("to second");
+
+    assert_eq!(*first.0.lock().unwrap(), ["INFO to first"]);
+    assert_eq!(*second.0.lock().unwrap(), ["DEBUG to second"]);
+
+    GlobalLogger::reset();
+    assert!(!GlobalLogger::is_initialized());
+    log::error!("dropped");
+    assert_eq!(second.0.lock().unwrap().len(), 1);
+}
+
+#[test]
+fn test_log_formatter() {
+    use std::io::{self, Write};
+
+    use log::{Level, LevelFilter, Log, Record};
+
+    struct MessageOnly;
+
+    impl LogFormatter for MessageOnly {
+        fn format(&self, out: &mut dyn Write, record: &Record, ctx: &FormatContext) -> io::Result<()> {
+            writeln!(out, "{} {}: {}", record.level(), ctx.extra.unwrap_or("-"), record.args())
+        }
+    }
+
+    let writer = MemoryWriter::default();
+    let logger = BaseLogger::<NopAppender, _>::new(LevelFilter::Info, writer.clone()).with_formatter(MessageOnly);
+    logger.log(&Record::builder().level(Level::Warn).args(format_args!("custom layout")).build());
+
+    assert_eq!(writer.contents(), "WARN -: custom layout\n");
+}
+
+#[test]
+fn test_json_formatter() {
+    use std::time::Duration;
+
+    use log::{Level, Record};
+
+    let ctx = FormatContext {
+        timestamp: Duration::from_secs(1792195200),
+        time: Some("2026-10-17T00:00:00.000Z"),
+        utc_offset: 0,
+        extra: Some("[PID: 1]"),
+        theme: None,
+    };
+    let record = Record::builder()
+        .level(Level::Error)
+        .target("app")
+        .module_path(Some("app::db"))
+        .line(Some(42))
+        .args(format_args!("say \"hi\"\n\tC:\\ \x1b[0m"))
+        .build();
+
+    assert_eq!(
+        format_line(&JsonFormatter, &record, &ctx),
+        r#"{"ts":"2026-10-17T00:00:00.000Z","level":"ERROR","target":"app","module":"app::db","file":null,"line":42,"msg":"say \"hi\"\n\tC:\\ \u001b[0m","extra":"[PID: 1]"}"#
+            .to_string()
+            + "\n"
+    );
+}
+
+#[test]
+fn test_logfmt_formatter() {
+    use std::time::Duration;
+
+    use log::{Level, Record};
+
+    let ctx = FormatContext { timestamp: Duration::from_secs(1792195200), ..TEST_CONTEXT };
+    let kvs = [("user_id", 5), ("attempts", 2)];
+    let record = Record::builder()
+        .level(Level::Info)
+        .module_path(Some("app::auth"))
+        .args(format_args!("user \"bob\" logged in"))
+        .key_values(&kvs)
+        .build();
+
+    assert_eq!(
+        format_line(&LogfmtFormatter, &record, &ctx),
+        "ts=T level=info module=app::auth msg=\"user \\\"bob\\\" logged in\" user_id=5 attempts=2\n"
+    );
+}
+
+#[test]
+fn test_pattern_formatter() {
+    use std::time::Duration;
+
+    use log::{Level, LevelFilter, Log, Record};
+
+    let formatter: PatternFormatter =
+        "{d(%F %H:%M:%S%.3f)} {l:>5} {M}:{L} {X(trace_id)} {{{a}}} - {m}{n}".parse().unwrap();
+    let ctx = FormatContext { timestamp: Duration::from_millis(1792206245123), extra: Some("pid=1"), ..TEST_CONTEXT };
+    let kvs = [("trace_id", "abc")];
+    let record = Record::builder()
+        .level(Level::Info)
+        .module_path(Some("app::db"))
+        .line(Some(7))
+        .key_values(&kvs)
+        .args(format_args!("connected"))
+        .build();
+    assert_eq!(
+        format_line(&formatter, &record, &ctx),
+        "2026-10-17 03:04:05.123  INFO app::db:7 abc {pid=1} - connected\n"
+    );
+
+    assert_eq!(PatternFormatter::new("{m} {q}").unwrap_err().position, 4);
+    assert!(PatternFormatter::new("{l:>x}").is_err());
+    assert!(PatternFormatter::new("{d(%Q)}").is_err());
+    assert!(PatternFormatter::new("{m").is_err());
+    assert!(PatternFormatter::new("m}").is_err());
+
+    // dates follow the logger's time zone
+    let writer = MemoryWriter::default();
+    let logger = BaseLogger::<NopAppender, _>::new(LevelFilter::Info, writer.clone())
+        .with_formatter(PatternFormatter::new("{d(%F %T%z)} {m}{n}").unwrap())
+        .with_timestamp(Timestamp::default().with_zone(TimeZone::fixed(8 * 3600)))
+        .with_clock(ManualClock::new(Duration::from_millis(1792206245123)));
+    logger.log(&Record::builder().level(Level::Info).args(format_args!("connected")).build());
+    assert_eq!(writer.contents(), "2026-10-17 11:04:05+0800 connected\n");
+}
+
+#[test]
+fn test_key_values() {
+    use log::{Level, Record, kv::ToValue};
+
+    let kvs: [(&str, &dyn ToValue); 3] = [("user_id", &5), ("admin", &false), ("name", &"bob b")];
+    let format = |formatter: &dyn LogFormatter| {
+        let record = Record::builder().level(Level::Info).args(format_args!("login")).key_values(&kvs).build();
+        format_line(formatter, &record, &TEST_CONTEXT)
+    };
+
+    assert!(format(&DefaultFormatter::new()).ends_with("- login user_id=5 admin=false name=bob b\n"));
+    assert!(
+        format(&JsonFormatter)
+            .ends_with("\"msg\":\"login\",\"fields\":{\"user_id\":5,\"admin\":false,\"name\":\"bob b\"}}\n")
+    );
+    assert!(format(&LogfmtFormatter).ends_with("msg=login user_id=5 admin=false name=\"bob b\"\n"));
+    assert_eq!(format(&PatternFormatter::new("{m}{K}").unwrap()), "login user_id=5 admin=false name=bob b");
+
+    // key-values can't overwrite the record's own keys
+    let kvs = [("msg", "x"), ("level", "y"), ("bad key", "z")];
+    let record = Record::builder().level(Level::Info).args(format_args!("hi")).key_values(&kvs).build();
+    assert!(
+        format_line(&JsonFormatter, &record, &TEST_CONTEXT)
+            .ends_with("\"msg\":\"hi\",\"fields\":{\"msg\":\"x\",\"level\":\"y\",\"bad key\":\"z\"}}\n")
+    );
+    assert!(
+        format_line(&LogfmtFormatter, &record, &TEST_CONTEXT)
+            .ends_with("msg=hi fields.msg=x fields.level=y bad_key=z\n")
+    );
+}
+
+#[test]
+fn test_source_location() {
+    use log::{Level, Record};
+
+    let format = |source_location, file| {
+        let formatter = DefaultFormatter::new().with_source_location(source_location);
+        let record = Record::builder()
+            .level(Level::Info)
+            .module_path(Some("hyper::client"))
+            .file(Some(file))
+            .line(Some(42))
+            .args(format_args!("connected"))
+            .build();
+        format_line(&formatter, &record, &TEST_CONTEXT)
+    };
+    let file = "/home/me/.cargo/registry/src/index.crates.io-1949cf8c6b5b557f/hyper-1.0.0/src/client.rs";
+
+    assert!(format(SourceLocation::Off, file).ends_with(" hyper::client] - connected\n"));
+    assert!(format(SourceLocation::Full, file).ends_with(&format!(" hyper::client {file}:42] - connected\n")));
+    assert!(format(SourceLocation::Short, file).ends_with(" hyper::client src/client.rs:42] - connected\n"));
+    assert!(format(SourceLocation::Short, "crates/app/src/main.rs").ends_with(" src/main.rs:42] - connected\n"));
+}
+
+#[test]
+fn test_timestamp() {
+    use std::time::Duration;
+
+    use log::{Level, Record};
+
+    let time = Duration::from_millis(1792206245123);
+    assert_eq!(Timestamp::default().render(time).unwrap(), "2026-10-17T03:04:05.123Z");
+    assert_eq!(Timestamp::default().with_precision(0).render(time).unwrap(), "2026-10-17T03:04:05Z");
+    assert_eq!(Timestamp::new(TimestampFormat::Rfc3339).render(time).unwrap(), "2026-10-17T03:04:05.123+00:00");
+    assert_eq!(Timestamp::new(TimestampFormat::UnixSeconds).render(time).unwrap(), "1792206245.123");
+    assert_eq!(Timestamp::new(TimestampFormat::UnixMillis).render(time).unwrap(), "1792206245123");
+    assert_eq!(Timestamp::none().render(time), None);
+
+    let shanghai = Timestamp::default().with_zone(TimeZone::fixed(8 * 3600));
+    assert_eq!(shanghai.render(time).unwrap(), "2026-10-17T11:04:05.123");
+    let shanghai = Timestamp::new(TimestampFormat::Rfc3339).with_zone(TimeZone::fixed(8 * 3600)).with_precision(6);
+    assert_eq!(shanghai.render(time).unwrap(), "2026-10-17T11:04:05.123000+08:00");
+
+    let rfc3339 = |zone: &str, secs| {
+        let zone = TimeZone::named(zone).unwrap();
+        Timestamp::new(TimestampFormat::Rfc3339).with_zone(zone).with_precision(0).render(Duration::from_secs(secs))
+    };
+    // 2026-10-17T03:04:05Z and 2026-01-15T00:00:00Z
+    assert_eq!(rfc3339("EST5EDT,M3.2.0,M11.1.0", 1792206245).unwrap(), "2026-10-16T23:04:05-04:00");
+    assert_eq!(rfc3339("EST5EDT,M3.2.0,M11.1.0", 1768435200).unwrap(), "2026-01-14T19:00:00-05:00");
+    assert_eq!(rfc3339("AEST-10AEDT,M10.1.0,M4.1.0/3", 1792206245).unwrap(), "2026-10-17T14:04:05+11:00");
+    assert_eq!(rfc3339("AEST-10AEDT,M10.1.0,M4.1.0/3", 1768435200).unwrap(), "2026-01-15T11:00:00+11:00");
+    assert_eq!(rfc3339("<+0530>-5:30", 1768435200).unwrap(), "2026-01-15T05:30:00+05:30");
+    // out of range offsets are rejected instead of overflowing
+    assert!(TimeZone::named("EST99999999").is_none());
+    assert!(TimeZone::named("EST168").is_none());
+    assert!(TimeZone::named("EST5:60").is_none());
+    assert!(TimeZone::named("<-167>167").is_some());
+    // a TZif header with counts larger than the file is rejected before allocating
+    let path = std::env::temp_dir().join(format!("rs_logger_tzif_{}", std::process::id()));
+    let mut tzif = b"TZif".to_vec();
+    tzif.resize(20, 0);
+    for count in [0, 0, 0, 0, u32::MAX, 0] {
+        tzif.extend_from_slice(&u32::to_be_bytes(count));
+    }
+    std::fs::write(&path, tzif).unwrap();
+    assert!(TimeZone::named(path.to_str().unwrap()).is_none());
+    std::fs::remove_file(&path).unwrap();
+
+    // JOURNAL_STREAM only counts when it names the stream itself
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+
+        let path = std::env::temp_dir().join(format!("rs_logger_journal_{}", std::process::id()));
+        let file = std::fs::File::create(&path).unwrap();
+        let meta = file.metadata().unwrap();
+        let stream = format!("{}:{}", meta.dev(), meta.ino());
+        assert!(logger::timestamp::is_journal_stream(stream.as_ref(), &file));
+        assert!(!logger::timestamp::is_journal_stream(format!("{}:0", meta.dev()).as_ref(), &file));
+        assert!(!logger::timestamp::is_journal_stream("journal".as_ref(), &file));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    let ctx = FormatContext { timestamp: time, time: None, ..TEST_CONTEXT };
+    let record = Record::builder().level(Level::Info).module_path(Some("app")).args(format_args!("started")).build();
+    let format = |formatter: &dyn LogFormatter| format_line(formatter, &record, &ctx);
+    assert!(format(&DefaultFormatter::new()).starts_with("["));
+    assert!(format(&DefaultFormatter::new()).ends_with(" app] - started\n"));
+    assert!(format(&JsonFormatter).starts_with("{\"level\":\"INFO\""));
+    assert!(format(&LogfmtFormatter).starts_with("level=info module=app"));
+}
+
+#[test]
+fn test_clock() {
+    use std::time::Duration;
+
+    use log::{Level, LevelFilter, Log, Record};
+
+    let clock = ManualClock::new(Duration::from_millis(1792206245123));
+    let writer = MemoryWriter::default();
+    let logger = BaseLogger::<NopAppender, _>::new(LevelFilter::Info, writer.clone())
+        .with_formatter(LogfmtFormatter)
+        .with_clock(clock.clone());
+    let record = |message| {
+        logger
+            .log(&Record::builder().level(Level::Info).module_path(Some("app")).args(format_args!("{message}")).build())
+    };
+    record("first");
+    clock.advance(Duration::from_secs(1));
+    record("second");
+
+    assert_eq!(
+        writer.contents(),
+        "ts=2026-10-17T03:04:05.123Z level=info module=app msg=first\n\
+         ts=2026-10-17T03:04:06.123Z level=info module=app msg=second\n"
+    );
+
+    let elapsed = ElapsedClock::new();
+    assert!(elapsed.now() <= elapsed.now());
+    assert!(elapsed.now() < Duration::from_secs(60));
+    assert!(SystemClock.now() > Duration::ZERO);
+}
+
+#[test]
+fn test_color_mode() {
+    use log::{Level, LevelFilter, Log, Record};
+
+    let log = |mode: Option<ColorMode>| {
+        let writer = MemoryWriter::default();
+        let logger = BaseLogger::<NopAppender, _>::new(LevelFilter::Info, writer.clone());
+        let logger = match mode {
+            Some(mode) => logger.with_color(mode),
+            None => logger,
+        };
+        logger.log(&Record::builder().level(Level::Warn).args(format_args!("disk full")).build());
+        writer.contents()
+    };
+
+    assert!(log(Some(ColorMode::Always)).contains(" \x1b[33mWARN\x1b[0m "));
+    assert!(log(Some(ColorMode::Never)).contains(" WARN "));
+    assert!(ColorMode::Always.resolve(false));
+    assert!(!ColorMode::Never.resolve(true));
+    if std::env::var_os("FORCE_COLOR").is_none() {
+        // MemoryWriter is not a terminal
+        assert!(log(None).contains(" WARN "));
+        assert!(!ColorMode::Auto.resolve(false));
+    }
+
+    // files are never coloured by default, even with FORCE_COLOR set
+    let path = std::env::temp_dir().join(format!("rs_logger_color_{}.log", std::process::id()));
+    let file = LogFileWriter::open(&path, &FileOptions::default()).unwrap();
+    assert!(!ColorMode::Auto.resolve_for(&file));
+    assert!(ColorMode::Always.resolve_for(&file));
+    assert!(!ColorMode::Auto.resolve_for(&vec![Target::Stderr, Target::File(file)]));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_theme() {
+    use log::{Level, Record};
+
+    assert_eq!(Style::new().fg(Color::BrightRed).bold().paint("ERROR").to_string(), "\x1b[91;1mERROR\x1b[0m");
+    assert_eq!(Style::new().fg(Color::Ansi256(208)).dim().paint("x").to_string(), "\x1b[38;5;208;2mx\x1b[0m");
+    assert_eq!(
+        Style::new().fg(Color::Rgb(1, 2, 3)).bg(Color::Blue).paint("x").to_string(),
+        "\x1b[38;2;1;2;3;44mx\x1b[0m"
+    );
+    assert_eq!(Style::new().paint("x").to_string(), "x");
+
+    let format = |theme: &Theme, level| {
+        let kvs = [("code", 5)];
+        let record = Record::builder()
+            .level(level)
+            .module_path(Some("app"))
+            .args(format_args!("failed"))
+            .key_values(&kvs)
+            .build();
+        format_line(&DefaultFormatter::new(), &record, &FormatContext { theme: Some(theme), ..TEST_CONTEXT })
+    };
+
+    assert_eq!(format(&Theme::default(), Level::Info), "[T \x1b[32mINFO\x1b[0m app] - failed code=5\n");
+    let theme = Theme::default()
+        .with_timestamp(Style::new().dim())
+        .with_module(Style::new().fg(Color::Magenta))
+        .with_message(Level::Error, Style::new().fg(Color::Red));
+    assert_eq!(
+        format(&theme, Level::Error),
+        "[\x1b[2mT\x1b[0m \x1b[91;1mERROR\x1b[0m \x1b[35mapp\x1b[0m] - \x1b[31mfailed code=5\x1b[0m\n"
+    );
+    assert!(format(&Theme::high_contrast(), Level::Warn).ends_with("- \x1b[93;1mfailed code=5\x1b[0m\n"));
+}
+
+#[test]
+fn test_filter() {
+    use log::{Level, LevelFilter, Log, Record};
+
+    let filter: Filter = "info, my_crate::db=trace,hyper=warn,my_crate::db::pool=off".parse().unwrap();
+    assert_eq!(filter.level("my_app"), LevelFilter::Info);
+    assert_eq!(filter.level("my_crate::db"), LevelFilter::Trace);
+    assert_eq!(filter.level("my_crate::db::query"), LevelFilter::Trace);
+    assert_eq!(filter.level("my_crate::db::pool::conn"), LevelFilter::Off);
+    assert_eq!(filter.level("my_crate::dbx"), LevelFilter::Info);
+    assert_eq!(filter.level("hyper::client"), LevelFilter::Warn);
+    assert_eq!(filter.max_level(), LevelFilter::Trace);
+
+    assert_eq!(Filter::parse("").unwrap(), Filter::new(LevelFilter::Error));
+    assert_eq!(Filter::parse("debug").unwrap().max_level(), LevelFilter::Debug);
+    assert_eq!(Filter::parse("my_crate").unwrap().level("my_crate::db"), LevelFilter::Trace);
+    assert_eq!(Filter::parse("my_crate").unwrap().level("other"), LevelFilter::Off);
+    assert_eq!(Filter::parse("info,hyper=loud").unwrap_err().directive, "hyper=loud");
+    assert!(Filter::parse("=info").is_err());
+    assert!(Filter::parse("not a module").is_err());
+    assert_eq!(Filter::parse("a::b::=info").unwrap_err().message, "invalid module path");
+    assert!(Filter::parse("a b=info").is_err());
+
+    let writer = MemoryWriter::default();
+    let logger = BaseLogger::<NopAppender, _>::new(LevelFilter::Info, writer.clone())
+        .with_color(ColorMode::Never)
+        .with_filter("warn,app::db=debug".parse().unwrap());
+    for (target, level) in [("app::db", Level::Debug), ("app::http", Level::Info), ("app::http", Level::Warn)] {
+        logger.log(
+            &Record::builder().level(level).target(target).module_path(Some(target)).args(format_args!("x")).build(),
+        );
+    }
+    let contents = writer.contents();
+    assert!(contents.contains("DEBUG app::db]"));
+    assert!(!contents.contains("INFO app::http]"));
+    assert!(contents.contains("WARN app::http]"));
+
+    let _lock = GLOBAL_LOGGER_LOCK.lock().unwrap();
+    Logger::new(LevelFilter::Info, Stderr).with_filter("warn,app::db=debug".parse().unwrap()).install();
+    assert_eq!(log::max_level(), LevelFilter::Debug);
+    GlobalLogger::reset();
+}
+
+#[test]
+fn test_init_from_env() {
+    use std::{collections::HashMap, fs};
+
+    use log::{Level, Log, Record};
+
+    let dir = std::env::temp_dir().join(format!("rs_logger_env_{}", std::process::id()));
+    let path = dir.join("app.log");
+    let vars = HashMap::from([
+        ("APP_LOG".to_string(), "warn,app::db=debug".to_string()),
+        ("APP_LOG_STYLE".to_string(), "always".to_string()),
+        ("APP_LOG_TIMESTAMP".to_string(), "unix_ms".to_string()),
+        ("APP_LOG_TARGET".to_string(), path.display().to_string()),
+    ]);
+    let logger = EnvLogger::from_vars("APP_LOG", |key| vars.get(key).cloned());
+    for (target, level) in [("app::db", Level::Debug), ("app::http", Level::Info)] {
+        logger.log(
+            &Record::builder().level(level).target(target).module_path(Some(target)).args(format_args!("x")).build(),
+        );
+    }
+    drop(logger);
+
+    let contents = fs::read_to_string(&path).unwrap();
+    let (time, rest) = contents.strip_prefix('[').unwrap().split_once(' ').unwrap();
+    assert!(time.parse::<u64>().is_ok());
+    assert_eq!(rest, "\x1b[34mDEBUG\x1b[0m app::db] - x\n");
+
+    // invalid values are ignored
+    let vars = HashMap::from([
+        ("APP_LOG".to_string(), "info,hyper=loud".to_string()),
+        ("APP_LOG_STYLE".to_string(), "sometimes".to_string()),
+        ("APP_LOG_TIMESTAMP".to_string(), "yesterday".to_string()),
+        ("APP_LOG_TIMEZONE".to_string(), "Mars/Olympus_Mons".to_string()),
+    ]);
+    let logger = EnvLogger::from_vars("APP_LOG", |key| vars.get(key).cloned());
+    assert!(logger.enabled(&log::Metadata::builder().level(Level::Error).build()));
+    assert!(!logger.enabled(&log::Metadata::builder().level(Level::Warn).build()));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_logger_handle() {
+    use log::LevelFilter;
+
+    let _lock = GLOBAL_LOGGER_LOCK.lock().unwrap();
+    let writer = MemoryWriter::default();
+    let handle = BaseLogger::<NopAppender, _>::new(LevelFilter::Info, writer.clone()).install();
+    log::debug!(target: "app::db", "hidden");
+
+    handle.clone().set_level(LevelFilter::Debug);
+    assert_eq!(log::max_level(), LevelFilter::Debug);
+    log::debug!(target: "app::db", "shown");
+
+    let saved = handle.filter().unwrap();
+    handle.set_filter("warn".parse().unwrap());
+    handle.set_directive("app::db", LevelFilter::Trace);
+    assert_eq!(log::max_level(), LevelFilter::Trace);
+    log::trace!(target: "app::db", "traced");
+    log::info!(target: "app::http", "dropped");
+
+    handle.set_filter(saved);
+    assert_eq!(log::max_level(), LevelFilter::Debug);
+
+    let contents = writer.contents();
+    assert!(!contents.contains("hidden"));
+    assert!(contents.contains("shown"));
+    assert!(contents.contains("traced"));
+    assert!(!contents.contains("dropped"));
+
+    // a replaced logger's handle no longer changes anything
+    Logger::init(LevelFilter::Warn);
+    handle.set_level(LevelFilter::Trace);
+    assert_eq!(log::max_level(), LevelFilter::Warn);
+    assert!(handle.filter().is_none());
+
+    // a handle still holding the filter of a replaced logger, as when it upgraded just before the replacement
+    let owner = logger::handle::FilterOwner::new(Filter::new(LevelFilter::Debug));
+    owner.set_installed();
+    let handle = LoggerHandle::new(&owner);
+    let held = std::sync::Arc::clone(&owner);
+    drop(owner);
+    handle.set_level(LevelFilter::Trace);
+    assert_eq!(log::max_level(), LevelFilter::Warn);
+    drop(held);
+    GlobalLogger::reset();
+}
+
+#[test]
+fn test_logger_builder() {
+    use std::io::Write;
+
+    use log::LevelFilter;
+
+    struct First;
+    struct Second;
+
+    impl LogAppender for First {
+        fn append<W: Write>(stream: &mut W) -> bool {
+            stream.write_all(b"[first]").is_ok()
+        }
+    }
+
+    impl LogAppender for Second {
+        fn append<W: Write>(stream: &mut W) -> bool {
+            stream.write_all(b"[second]").is_ok()
+        }
+    }
+
+    let _lock = GLOBAL_LOGGER_LOCK.lock().unwrap();
+    let (first, second) = (MemoryWriter::default(), MemoryWriter::default());
+    let handle = LoggerBuilder::new()
+        .level(LevelFilter::Debug)
+        .writer(first.clone())
+        .writer(second.clone())
+        .formatter(PatternFormatter::new("{l} {a} {m}{n}").unwrap())
+        .color(ColorMode::Never)
+        .appender::<First>()
+        .appender::<Second>()
+        .install();
+    log::debug!("shown");
+    log::trace!("hidden");
+    handle.set_level(LevelFilter::Trace);
+    log::trace!("traced");
+
+    assert_eq!(first.contents(), "DEBUG [first] [second] shown\nTRACE [first] [second] traced\n");
+    assert_eq!(second.contents(), first.contents());
+    GlobalLogger::reset();
+}
+
+#[test]
+fn test_tee() {
+    use log::LevelFilter;
+
+    let _lock = GLOBAL_LOGGER_LOCK.lock().unwrap();
+    let (errors, everything) = (MemoryWriter::default(), MemoryWriter::default());
+    let tee = Tee::new()
+        .with_sink(Sink::new(errors.clone()).with_level(LevelFilter::Error).with_color(ColorMode::Always))
+        .with_sink(Sink::new(everything.clone()).with_formatter(LogfmtFormatter));
+    BaseLogger::<NopAppender, _>::new(LevelFilter::Debug, tee).with_timestamp(Timestamp::none()).install();
+    log::debug!("starting");
+    log::error!("failed");
+    log::trace!("hidden");
+
+    let errors = errors.contents();
+    assert!(errors.contains("\x1b[91;1mERROR"));
+    assert!(errors.contains("failed"));
+    assert!(!errors.contains("starting"));
+    let everything = everything.contents();
+    assert!(everything.contains("level=debug") && everything.contains("msg=starting"));
+    assert!(everything.contains("level=error") && everything.contains("msg=failed"));
+    assert!(!everything.contains("hidden"));
+    GlobalLogger::reset();
+}
+
+#[test]
+fn test_split_writer() {
+    use log::{Level, LevelFilter};
+
+    let _lock = GLOBAL_LOGGER_LOCK.lock().unwrap();
+    let (low, high) = (MemoryWriter::default(), MemoryWriter::default());
+    BaseLogger::<NopAppender, _>::new(LevelFilter::Debug, SplitWriter::with_writers(low.clone(), high.clone()))
+        .install();
+    log::info!("started");
+    log::warn!("slow request");
+    log::error!("failed");
+    assert!(low.contents().contains("started"));
+    assert!(!low.contents().contains("slow request"));
+    assert!(high.contents().contains("slow request") && high.contents().contains("failed"));
+
+    let (low, high) = (MemoryWriter::default(), MemoryWriter::default());
+    let writer = SplitWriter::with_writers(low.clone(), high.clone()).with_threshold(Level::Error);
+    BaseLogger::<NopAppender, _>::new(LevelFilter::Debug, writer).install();
+    log::warn!("slow request");
+    log::error!("failed");
+    assert!(low.contents().contains("slow request"));
+    assert!(!high.contents().contains("slow request") && high.contents().contains("failed"));
+    GlobalLogger::reset();
+}
+
+#[test]
+fn test_async_writer() {
+    use std::time::Duration;
+
+    use log::{Level, LevelFilter, Log, Record};
+
+    fn log_records(logger: &impl Log, count: usize) {
+        for i in 0..count {
+            logger.log(&Record::builder().args(format_args!("record {i}")).level(Level::Info).build());
+        }
+    }
+
+    let memory = MemoryWriter::default();
+    let writer = AsyncWriter::new(memory.clone(), 2).unwrap();
+    let handle = writer.handle();
+    let logger = BaseLogger::<NopAppender, _>::new(LevelFilter::Info, writer);
+    log_records(&logger, 100);
+    logger.flush();
+    assert_eq!(memory.contents().lines().count(), 100);
+    assert_eq!(handle.dropped(), 0);
+
+    // the thread still routes by level
+    let (low, high) = (MemoryWriter::default(), MemoryWriter::default());
+    let writer = AsyncWriter::new(SplitWriter::with_writers(low.clone(), high.clone()), 8).unwrap();
+    let logger = BaseLogger::<NopAppender, _>::new(LevelFilter::Info, writer);
+    logger.log(&Record::builder().args(format_args!("started")).level(Level::Info).build());
+    logger.log(&Record::builder().args(format_args!("failed")).level(Level::Error).build());
+    logger.flush();
+    assert!(low.contents().contains("started") && !low.contents().contains("failed"));
+    assert!(high.contents().contains("failed") && !high.contents().contains("started"));
+
+    let (errors, all) = (MemoryWriter::default(), MemoryWriter::default());
+    let tee = Tee::new()
+        .with_sink(Sink::new(errors.clone()).with_level(LevelFilter::Error))
+        .with_sink(Sink::new(all.clone()));
+    let logger = BaseLogger::<NopAppender, _>::new(LevelFilter::Info, AsyncWriter::new(tee, 8).unwrap());
+    logger.log(&Record::builder().args(format_args!("started")).level(Level::Info).build());
+    logger.flush();
+    assert_eq!(errors.contents(), "");
+    assert!(all.contents().contains("started"));
+
+    for overflow in [Overflow::DropNewest, Overflow::DropOldest] {
+        let memory = MemoryWriter::default();
+        let writer =
+            AsyncWriter::new(memory.clone(), 2).unwrap().with_overflow(overflow).with_report_interval(Duration::ZERO);
+        let handle = writer.handle();
+        let logger = BaseLogger::<NopAppender, _>::new(LevelFilter::Info, writer);
+
+        // the writer thread blocks on the first line it takes, so the queue fills up
+        let stalled = memory.0.lock().unwrap();
+        log_records(&logger, 10);
+        drop(stalled);
+        handle.flush();
+
+        let contents = memory.contents();
+        assert!(handle.dropped() >= 6);
+        assert_eq!(contents.lines().count() as u64 + handle.dropped(), 10);
+        if overflow == Overflow::DropNewest {
+            assert!(contents.contains("record 0") && !contents.contains("record 9"));
+        } else {
+            assert!(contents.contains("record 9"));
+        }
+    }
+}
+
+#[cfg(feature = "config")]
+#[test]
+fn test_config() {
+    use std::{fs, time::Duration};
+
+    use log::{Level, LevelFilter, Log, Record};
+
+    let dir = std::env::temp_dir().join(format!("rs_logger_config_{}", std::process::id()));
+    let path = dir.join("app.log");
+    let config: Config = format!(
+        r#"
+level = "warn"
+format = "json"
+
+[modules]
+"app::db" = "debug"
+
+[timestamp]
+format = "none"
+
+[[writers]]
+kind = "file"
+path = "{}"
+"#,
+        path.display()
+    )
+    .parse()
+    .unwrap();
+    assert_eq!(config.filter().level("app::db::pool"), LevelFilter::Debug);
+    assert_eq!(config.filter().level("app::http"), LevelFilter::Warn);
+
+    let logger = config.build().unwrap();
+    for (target, level) in [("app::db", Level::Debug), ("app::http", Level::Info)] {
+        logger.log(&Record::builder().level(level).target(target).args(format_args!("x")).build());
+    }
+    drop(logger);
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        "{\"level\":\"DEBUG\",\"target\":\"app::db\",\"module\":null,\"file\":null,\"line\":null,\"msg\":\"x\"}\n"
+    );
+
+    let error = |source: &str| match Config::parse(source).unwrap_err() {
+        ConfigError::Invalid { line, column, message } => (line, column, message),
+        err => panic!("{err}"),
+    };
+    assert_eq!(error("level = \"info\"\n\n[modules]\nhyper = \"loud\"").0, 4);
+    assert_eq!(error("[modules]\n\"a b\" = \"info\""), (2, 1, "invalid module path `a b`".to_string()));
+    assert_eq!(error("level = \"info\"\ncolour = \"auto\"").0, 2);
+    assert_eq!(error("[[writers]]\nkind = \"file\"\nmax_bytes = 1\nrotation = \"daily\"").0, 3);
+    assert_eq!(error("[[writers]]\nkind = \"file\"").2, "a file writer needs a `path`");
+    assert_eq!(error("pattern = \"{l} {q}\"").1, 16);
+    assert_eq!(error("pattern = '{l} {q}'").1, 16);
+    // escapes shift the position, so the error points at the string
+    assert_eq!(error("pattern = \"\\t日{q}\"").1, 11);
+    assert_eq!(error("pattern = '''\n{q}'''").1, 11);
+    assert_eq!(error("[timestamp]\nprecision = 12").0, 2);
+
+    // only levels are reloaded
+    let config_path = dir.join("log.toml");
+    fs::write(&config_path, "level = \"info\"").unwrap();
+    let config = Config::load(&config_path).unwrap();
+    let logger = config.build().unwrap();
+    let watcher = config.watch(&config_path, logger.handle(), Duration::from_millis(10)).unwrap();
+    fs::write(&config_path, "level = \"info,app=trace\"\n").unwrap();
+    for _ in 0..500 {
+        if logger.handle().filter().unwrap().level("app") == LevelFilter::Trace {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    assert_eq!(logger.handle().filter().unwrap().level("app"), LevelFilter::Trace);
+    drop(watcher);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
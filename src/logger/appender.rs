@@ -14,3 +14,19 @@ impl LogAppender for NopAppender {
         false
     }
 }
+
+/// A pair of appenders writes both, separated by a space, e.g. `BaseLogger<(PidAppender, ThreadAppender)>`
+impl<A: LogAppender, B: LogAppender> LogAppender for (A, B) {
+    fn append<W: Write>(stream: &mut W) -> bool {
+        let first = A::append(stream);
+        let mut second = Vec::new();
+        if !B::append(&mut second) {
+            return first;
+        }
+        if first {
+            let _ = stream.write_all(b" ");
+        }
+        let _ = stream.write_all(&second);
+        true
+    }
+}
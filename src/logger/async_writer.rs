@@ -0,0 +1,301 @@
+use std::{
+    collections::VecDeque,
+    io,
+    io::Write,
+    mem,
+    sync::{
+        Arc, Condvar, Mutex, MutexGuard,
+        atomic::{AtomicU64, Ordering},
+    },
+    thread,
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+
+use log::{Level, Record};
+
+use super::{formatter::*, writer::*};
+
+/// Overflow decides what happens to a record when the queue of an [`AsyncWriter`] is full
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Overflow {
+    /// Wait until the writer thread makes room, no record is lost
+    #[default]
+    Block,
+    /// Discard the record being logged
+    DropNewest,
+    /// Discard the oldest queued record to make room
+    DropOldest,
+}
+
+/// AsyncWriter moves the I/O of another writer to a background thread. Records are still formatted on the calling
+/// thread, then the bytes are queued, so logging only blocks when the queue is full and the overflow policy is
+/// [`Overflow::Block`]:
+///
+/// ```rust
+/// use log::LevelFilter;
+/// use rs_logger::{AsyncWriter, BaseLogger, NopAppender, Overflow, Stderr};
+///
+/// let writer = AsyncWriter::new(Stderr, 8192).unwrap().with_overflow(Overflow::DropNewest);
+/// let handle = writer.handle();
+/// BaseLogger::<NopAppender, _>::new(LevelFilter::Info, writer).install();
+/// log::info!("handled request");
+/// // queued records are only written once the thread gets to them, flush before exiting
+/// log::logger().flush();
+/// assert_eq!(handle.dropped(), 0);
+/// ```
+///
+/// The thread hands each line to the wrapped writer with its level through [`LogWriter::write_line`], so a
+/// [`SplitWriter`] or a list of writers still routes by level. A [`Tee`](super::tee::Tee) also skips the sinks
+/// that don't take the level, but the line is formatted once by the logger's formatter, wrap the writer of each
+/// [`Sink`](super::tee::Sink) instead to keep their own formatters and colors.
+///
+/// Dropped records are counted and reported through `log_print!` every 10 seconds, see
+/// [`AsyncWriter::with_report_interval`]. Dropping the writer, such as when its logger is replaced, writes what
+/// is left in the queue and stops the thread
+pub struct AsyncWriter {
+    queue: Arc<Queue>,
+    overflow: Overflow,
+    terminal: bool,
+    file: bool,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl AsyncWriter {
+    /// Write to `writer` from a thread, holding at most `capacity` records in the queue. Fails if the thread
+    /// can't be spawned
+    pub fn new<W: LogWriter>(writer: W, capacity: usize) -> io::Result<Self> {
+        let queue = Arc::new(Queue {
+            state: Mutex::new(State { lines: VecDeque::new(), writing: false, closed: false }),
+            capacity: capacity.max(1),
+            queued: Condvar::new(),
+            written: Condvar::new(),
+            dropped: AtomicU64::new(0),
+            report_interval_ms: AtomicU64::new(10_000),
+        });
+        let (terminal, file) = (writer.is_terminal(), writer.is_file());
+        let thread = {
+            let queue = queue.clone();
+            thread::Builder::new().name("rs_logger-async".to_string()).spawn(move || queue.run(writer))?
+        };
+        Ok(Self { queue, overflow: Overflow::default(), terminal, file, thread: Some(thread) })
+    }
+
+    /// What to do with a record when the queue is full, [`Overflow::Block`] by default
+    pub fn with_overflow(mut self, overflow: Overflow) -> Self {
+        self.overflow = overflow;
+        self
+    }
+
+    /// How often the number of newly dropped records is reported, a zero interval turns reports off
+    pub fn with_report_interval(self, interval: Duration) -> Self {
+        self.queue.report_interval_ms.store(interval.as_millis() as u64, Ordering::Relaxed);
+        self
+    }
+
+    /// A handle to read the number of dropped records and flush the queue after the writer has moved into
+    /// a logger
+    pub fn handle(&self) -> AsyncHandle {
+        AsyncHandle { queue: self.queue.clone() }
+    }
+}
+
+impl LogWriter for AsyncWriter {
+    type Stream = AsyncStream;
+
+    /// Bytes are queued as one record when the stream is flushed or dropped
+    fn get(&self) -> Self::Stream {
+        AsyncStream { queue: self.queue.clone(), overflow: self.overflow, line: Vec::new() }
+    }
+
+    fn is_terminal(&self) -> bool {
+        self.terminal
+    }
+
+    fn is_file(&self) -> bool {
+        self.file
+    }
+
+    fn write_record(&self, record: &Record, ctx: &FormatContext, formatter: &dyn LogFormatter) -> io::Result<()> {
+        let mut line = Vec::with_capacity(256);
+        formatter.format(&mut line, record, ctx)?;
+        self.queue.push(Some(record.level()), line, self.overflow);
+        Ok(())
+    }
+
+    fn write_line(&self, level: Level, line: &[u8]) -> io::Result<()> {
+        self.queue.push(Some(level), line.to_vec(), self.overflow);
+        Ok(())
+    }
+
+    fn sync(&self) -> io::Result<()> {
+        self.queue.wait_written();
+        Ok(())
+    }
+}
+
+impl Drop for AsyncWriter {
+    fn drop(&mut self) {
+        self.queue.lock().closed = true;
+        self.queue.queued.notify_all();
+        self.queue.written.notify_all();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// AsyncHandle reads the state of an [`AsyncWriter`] that has moved into a logger
+#[derive(Clone)]
+pub struct AsyncHandle {
+    queue: Arc<Queue>,
+}
+
+impl AsyncHandle {
+    /// Number of records discarded because the queue was full, since the writer was created
+    pub fn dropped(&self) -> u64 {
+        self.queue.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Wait until every queued record has been written and flushed
+    pub fn flush(&self) {
+        self.queue.wait_written();
+    }
+}
+
+/// AsyncStream collects bytes written through [`AsyncWriter::get`] and queues them as one record
+pub struct AsyncStream {
+    queue: Arc<Queue>,
+    overflow: Overflow,
+    line: Vec<u8>,
+}
+
+impl Write for AsyncStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.line.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if !self.line.is_empty() {
+            self.queue.push(None, mem::take(&mut self.line), self.overflow);
+        }
+        Ok(())
+    }
+}
+
+impl Drop for AsyncStream {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
+struct Queue {
+    state: Mutex<State>,
+    capacity: usize,
+    /// Signalled when a record is queued or the writer is closed
+    queued: Condvar,
+    /// Signalled when the thread has taken records from the queue or finished writing them
+    written: Condvar,
+    dropped: AtomicU64,
+    report_interval_ms: AtomicU64,
+}
+
+struct State {
+    /// Formatted lines and their level, raw bytes from [`AsyncWriter::get`] have none
+    lines: VecDeque<(Option<Level>, Vec<u8>)>,
+    /// The thread has taken records that aren't flushed yet
+    writing: bool,
+    closed: bool,
+}
+
+impl Queue {
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    fn push(&self, level: Option<Level>, line: Vec<u8>, overflow: Overflow) {
+        let mut state = self.lock();
+        while state.lines.len() >= self.capacity && !state.closed {
+            match overflow {
+                Overflow::Block => state = self.written.wait(state).unwrap_or_else(|err| err.into_inner()),
+                Overflow::DropNewest => {
+                    self.dropped.fetch_add(1, Ordering::Relaxed);
+                    return;
+                }
+                Overflow::DropOldest => {
+                    state.lines.pop_front();
+                    self.dropped.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        }
+        if state.closed {
+            return;
+        }
+        state.lines.push_back((level, line));
+        self.queued.notify_one();
+    }
+
+    fn wait_written(&self) {
+        let mut state = self.lock();
+        while (!state.lines.is_empty() || state.writing) && !state.closed {
+            state = self.written.wait(state).unwrap_or_else(|err| err.into_inner());
+        }
+    }
+
+    /// Body of the writer thread, returns once the writer is closed and the queue is empty
+    fn run<W: LogWriter>(&self, writer: W) {
+        let mut reported = 0;
+        let mut last_report = Instant::now();
+        loop {
+            let batch = {
+                let mut state = self.lock();
+                if state.lines.is_empty() && !state.closed {
+                    let interval = self.report_interval();
+                    let timeout = if interval.is_zero() { Duration::from_secs(3600) } else { interval };
+                    state = self.queued.wait_timeout(state, timeout).unwrap_or_else(|err| err.into_inner()).0;
+                }
+                if state.lines.is_empty() && state.closed {
+                    break;
+                }
+                state.writing = !state.lines.is_empty();
+                mem::take(&mut state.lines)
+            };
+
+            if !batch.is_empty() {
+                self.written.notify_all();
+                for (level, line) in &batch {
+                    let _ = match level {
+                        Some(level) => writer.write_line(*level, line),
+                        None => {
+                            let mut stream = writer.get();
+                            stream.write_all(line).and_then(|_| stream.flush())
+                        }
+                    };
+                }
+                self.lock().writing = false;
+                self.written.notify_all();
+            }
+            // outside the lock, so a slow stderr never blocks the threads that log
+            self.report_dropped(&mut reported, &mut last_report, false);
+        }
+        self.report_dropped(&mut reported, &mut last_report, true);
+    }
+
+    fn report_interval(&self) -> Duration {
+        Duration::from_millis(self.report_interval_ms.load(Ordering::Relaxed))
+    }
+
+    fn report_dropped(&self, reported: &mut u64, last_report: &mut Instant, force: bool) {
+        let interval = self.report_interval();
+        if interval.is_zero() || (!force && last_report.elapsed() < interval) {
+            return;
+        }
+        *last_report = Instant::now();
+        let dropped = self.dropped.load(Ordering::Relaxed);
+        if dropped > *reported {
+            crate::log_print!(Level::Warn, "dropped {} log records, the queue was full", dropped - *reported);
+            *reported = dropped;
+        }
+    }
+}
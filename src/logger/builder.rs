@@ -0,0 +1,146 @@
+use std::marker::PhantomData;
+
+use log::LevelFilter;
+
+use super::{
+    appender::*, clock::*, error::*, filter::*, formatter::*, handle::*, logger::*, theme::*, timestamp::*, writer::*,
+};
+
+/// Logger built by LoggerBuilder, its writers and formatter are picked at runtime
+pub type DynLogger<A = NopAppender> = BaseLogger<A, Vec<BoxWriter>, Box<dyn LogFormatter>>;
+
+/// LoggerBuilder composes a logger at runtime, for choices such as stdout in development and a file in production:
+///
+/// ```rust
+/// use log::LevelFilter;
+/// use rs_logger::{LoggerBuilder, JsonFormatter, Stdout, Stderr};
+///
+/// let production = false;
+/// let builder = LoggerBuilder::new().level(LevelFilter::Debug);
+/// let builder = if production { builder.writer(Stderr).formatter(JsonFormatter) } else { builder.writer(Stdout) };
+/// let handle = builder.install();
+/// ```
+///
+/// The writers and formatter are boxed, `BaseLogger` with concrete types remains the zero cost option
+pub struct LoggerBuilder<A: LogAppender = NopAppender> {
+    filter: Filter,
+    writers: Vec<BoxWriter>,
+    formatter: Option<Box<dyn LogFormatter>>,
+    color: ColorMode,
+    theme: Theme,
+    timestamp: Timestamp,
+    clock: Option<Box<dyn Clock>>,
+    _appender: PhantomData<A>,
+}
+
+impl LoggerBuilder {
+    /// A builder for an info level logger writing to stderr
+    pub fn new() -> Self {
+        Self {
+            filter: Filter::new(LevelFilter::Info),
+            writers: Vec::new(),
+            formatter: None,
+            color: ColorMode::default(),
+            theme: Theme::default(),
+            timestamp: Timestamp::default(),
+            clock: None,
+            _appender: PhantomData,
+        }
+    }
+}
+
+impl Default for LoggerBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<A: LogAppender> LoggerBuilder<A> {
+    pub fn level(mut self, level: LevelFilter) -> Self {
+        self.filter = Filter::new(level);
+        self
+    }
+
+    /// Per-module levels, see [`Filter`]
+    pub fn filter(mut self, filter: Filter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Add a writer, every line goes to all writers. Stderr is used when none is added
+    pub fn writer<W>(mut self, writer: W) -> Self
+    where
+        W: LogWriter,
+        W::Stream: 'static,
+    {
+        self.writers.push(BoxWriter::new(writer));
+        self
+    }
+
+    pub fn formatter(mut self, formatter: impl LogFormatter) -> Self {
+        self.formatter = Some(Box::new(formatter));
+        self
+    }
+
+    /// Add an appender after the ones already added
+    pub fn appender<B: LogAppender>(self) -> LoggerBuilder<(A, B)> {
+        LoggerBuilder {
+            filter: self.filter,
+            writers: self.writers,
+            formatter: self.formatter,
+            color: self.color,
+            theme: self.theme,
+            timestamp: self.timestamp,
+            clock: self.clock,
+            _appender: PhantomData,
+        }
+    }
+
+    pub fn color(mut self, color: ColorMode) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    pub fn timestamp(mut self, timestamp: Timestamp) -> Self {
+        self.timestamp = timestamp;
+        self
+    }
+
+    pub fn clock(mut self, clock: impl Clock) -> Self {
+        self.clock = Some(Box::new(clock));
+        self
+    }
+
+    pub fn build(mut self) -> DynLogger<A> {
+        if self.writers.is_empty() {
+            self.writers.push(BoxWriter::new(Stderr));
+        }
+        let formatter = self.formatter.unwrap_or_else(|| Box::new(DefaultFormatter::new()));
+        let logger = BaseLogger::new(LevelFilter::Off, self.writers)
+            .with_filter(self.filter)
+            .with_color(self.color)
+            .with_theme(self.theme)
+            .with_timestamp(self.timestamp)
+            .with_formatter(formatter);
+        match self.clock {
+            Some(clock) => logger.with_clock(clock),
+            None => logger,
+        }
+    }
+
+    /// Build the logger and install it, replacing the active one. Panics if a logger from another crate is
+    /// already installed
+    pub fn install(self) -> LoggerHandle {
+        self.build().install()
+    }
+
+    /// Build the logger and install it unless one is already active
+    pub fn try_install(self) -> Result<LoggerHandle, InitError> {
+        self.build().try_install()
+    }
+}
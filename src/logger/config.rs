@@ -0,0 +1,403 @@
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    ops::Range,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::mpsc::{self, RecvTimeoutError, Sender},
+    thread::{self, JoinHandle},
+    time::{Duration, SystemTime},
+};
+
+use log::{Level, LevelFilter};
+use serde::Deserialize;
+use toml::Spanned;
+
+use super::{
+    appender::*, env::*, error::*, filter::*, formatter::*, handle::*, json::*, logfmt::*, logger::*, pattern::*,
+    timestamp::*, writer::*,
+};
+
+/// Config describes a whole logger in TOML:
+///
+/// ```toml
+/// level = "info,hyper=warn"      # directives, see Filter. Defaults to info
+/// format = "default"             # default, json, logfmt or pattern
+/// pattern = "{d} {l} {M} - {m}{n}"
+/// source_location = "short"      # off, full or short, for the default format
+/// color = "auto"                 # auto, always or never
+///
+/// [modules]                      # more directives
+/// "my_crate::db" = "trace"
+///
+/// [timestamp]
+/// format = "rfc3339"             # iso, rfc3339, unix, unix_ms or none
+/// timezone = "local"             # utc, local or a zone such as Asia/Shanghai
+/// precision = 3
+///
+/// [[writers]]                    # stderr when there are none
+/// kind = "stderr"                # stderr, stdout or file
+///
+/// [[writers]]
+/// kind = "file"
+/// path = "/var/log/app.log"
+/// max_bytes = 10485760           # rotate by size, keeping max_backups files, 5 by default
+/// rotation = "daily"             # or rotate at UTC day / hour boundaries
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Config {
+    filter: Filter,
+    format: Format,
+    color: ColorMode,
+    timestamp: Timestamp,
+    writers: Vec<WriterConfig>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Format {
+    Default(DefaultFormatter),
+    Json,
+    Logfmt,
+    Pattern(PatternFormatter),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum WriterConfig {
+    Stderr,
+    Stdout,
+    File(PathBuf),
+    RotatingFile { path: PathBuf, max_bytes: u64, max_backups: usize },
+    TimeRotatingFile { path: PathBuf, rotation: Rotation },
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawConfig {
+    level: Option<Spanned<String>>,
+    #[serde(default)]
+    modules: BTreeMap<Spanned<String>, Spanned<String>>,
+    format: Option<Spanned<String>>,
+    pattern: Option<Spanned<String>>,
+    source_location: Option<Spanned<String>>,
+    color: Option<Spanned<String>>,
+    timestamp: Option<RawTimestamp>,
+    #[serde(default)]
+    writers: Vec<Spanned<RawWriter>>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawTimestamp {
+    format: Option<Spanned<String>>,
+    timezone: Option<Spanned<String>>,
+    precision: Option<Spanned<usize>>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawWriter {
+    kind: Spanned<String>,
+    path: Option<PathBuf>,
+    max_bytes: Option<Spanned<u64>>,
+    max_backups: Option<usize>,
+    rotation: Option<Spanned<String>>,
+}
+
+impl Config {
+    /// Read and validate the config file at `path`
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    pub fn parse(source: &str) -> Result<Self, ConfigError> {
+        let invalid = |span: Range<usize>, message: String| {
+            let before = &source[..source.floor_char_boundary(span.start)];
+            let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+            ConfigError::Invalid {
+                line: before.matches('\n').count() + 1,
+                column: before[line_start..].chars().count() + 1,
+                message,
+            }
+        };
+
+        let raw: RawConfig = toml::from_str(source)
+            .map_err(|err| invalid(err.span().unwrap_or(0..0), err.message().trim_end().to_string()))?;
+
+        let mut filter = match &raw.level {
+            Some(level) => Filter::parse(level.get_ref()).map_err(|err| invalid(level.span(), err.to_string()))?,
+            None => Filter::new(LevelFilter::Info),
+        };
+        for (module, level) in &raw.modules {
+            if !is_module_path(module.get_ref()) {
+                return Err(invalid(module.span(), format!("invalid module path `{}`", module.get_ref())));
+            }
+            let parsed = level
+                .get_ref()
+                .parse()
+                .map_err(|_| invalid(level.span(), format!("unknown level `{}`", level.get_ref())))?;
+            filter = filter.with_directive(module.get_ref(), parsed);
+        }
+
+        let mut default = DefaultFormatter::new();
+        if let Some(location) = &raw.source_location {
+            let location = match location.get_ref().as_str() {
+                "off" => SourceLocation::Off,
+                "full" => SourceLocation::Full,
+                "short" => SourceLocation::Short,
+                other => {
+                    return Err(invalid(
+                        location.span(),
+                        format!("unknown source_location `{other}`, expected off, full or short"),
+                    ));
+                }
+            };
+            default = default.with_source_location(location);
+        }
+        let format = match (&raw.format, &raw.pattern) {
+            (None, None) => Format::Default(default),
+            (Some(format), pattern) => match (format.get_ref().as_str(), pattern) {
+                ("default", None) => Format::Default(default),
+                ("json", None) => Format::Json,
+                ("logfmt", None) => Format::Logfmt,
+                ("pattern", Some(pattern)) => {
+                    Self::parse_pattern(source, pattern).map_err(|(span, message)| invalid(span, message))?
+                }
+                ("pattern", None) => return Err(invalid(format.span(), "format `pattern` needs a `pattern`".into())),
+                ("default" | "json" | "logfmt", Some(pattern)) => {
+                    return Err(invalid(pattern.span(), "`pattern` is only used with format `pattern`".into()));
+                }
+                (other, _) => {
+                    return Err(invalid(
+                        format.span(),
+                        format!("unknown format `{other}`, expected default, json, logfmt or pattern"),
+                    ));
+                }
+            },
+            (None, Some(pattern)) => {
+                Self::parse_pattern(source, pattern).map_err(|(span, message)| invalid(span, message))?
+            }
+        };
+
+        let color = match &raw.color {
+            Some(color) => parse_color_mode(color.get_ref()).ok_or_else(|| {
+                invalid(color.span(), format!("unknown color `{}`, expected auto, always or never", color.get_ref()))
+            })?,
+            None => ColorMode::default(),
+        };
+
+        let mut timestamp = Timestamp::default();
+        if let Some(raw) = &raw.timestamp {
+            if let Some(format) = &raw.format {
+                timestamp = parse_timestamp(format.get_ref()).ok_or_else(|| {
+                    invalid(
+                        format.span(),
+                        format!(
+                            "unknown timestamp format `{}`, expected iso, rfc3339, unix, unix_ms or none",
+                            format.get_ref()
+                        ),
+                    )
+                })?;
+            }
+            if let Some(precision) = &raw.precision {
+                if *precision.get_ref() > 9 {
+                    return Err(invalid(precision.span(), "precision must be between 0 and 9".into()));
+                }
+                timestamp = timestamp.with_precision(*precision.get_ref());
+            }
+            if let Some(zone) = &raw.timezone {
+                let parsed = parse_time_zone(zone.get_ref())
+                    .ok_or_else(|| invalid(zone.span(), format!("unknown time zone `{}`", zone.get_ref())))?;
+                timestamp = timestamp.with_zone(parsed);
+            }
+        }
+
+        let writers = raw
+            .writers
+            .iter()
+            .map(|writer| Self::parse_writer(writer).map_err(|(span, message)| invalid(span, message)))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self { filter, format, color, timestamp, writers })
+    }
+
+    fn parse_pattern(source: &str, pattern: &Spanned<String>) -> Result<Format, (Range<usize>, String)> {
+        let span = pattern.span();
+        match PatternFormatter::new(pattern.get_ref()) {
+            Ok(formatter) => Ok(Format::Pattern(formatter)),
+            Err(err) => {
+                // the position only maps into the source when the string is written as is, without escapes or
+                // multiline quotes, otherwise point at the whole string
+                let raw = source.get(span.clone()).unwrap_or_default();
+                let verbatim = [('"', '"'), ('\'', '\'')].iter().any(|&(open, close)| {
+                    raw.strip_prefix(open).and_then(|raw| raw.strip_suffix(close)) == Some(pattern.get_ref().as_str())
+                });
+                let start = if verbatim { span.start + 1 + err.position } else { span.start };
+                Err((start..span.end, err.to_string()))
+            }
+        }
+    }
+
+    fn parse_writer(writer: &Spanned<RawWriter>) -> Result<WriterConfig, (Range<usize>, String)> {
+        let span = writer.span();
+        let writer = writer.get_ref();
+        let kind = &writer.kind;
+        let path = || writer.path.clone().ok_or_else(|| (span.clone(), "a file writer needs a `path`".to_string()));
+        let not_rotating = || (span.clone(), format!("`{}` writers don't rotate", kind.get_ref()));
+
+        match kind.get_ref().as_str() {
+            "stderr" | "stdout" if writer.path.is_some() => {
+                Err((span, format!("`{}` writers have no path", kind.get_ref())))
+            }
+            "stderr" | "stdout" if writer.max_bytes.is_some() || writer.rotation.is_some() => Err(not_rotating()),
+            "stderr" => Ok(WriterConfig::Stderr),
+            "stdout" => Ok(WriterConfig::Stdout),
+            "file" => match (&writer.max_bytes, &writer.rotation) {
+                (Some(max_bytes), Some(_)) => {
+                    Err((max_bytes.span(), "rotate either by size with `max_bytes` or by time with `rotation`".into()))
+                }
+                (Some(max_bytes), None) => Ok(WriterConfig::RotatingFile {
+                    path: path()?,
+                    max_bytes: *max_bytes.get_ref(),
+                    max_backups: writer.max_backups.unwrap_or(5),
+                }),
+                (None, Some(rotation)) => {
+                    let parsed = match rotation.get_ref().as_str() {
+                        "daily" => Rotation::Daily,
+                        "hourly" => Rotation::Hourly,
+                        other => {
+                            return Err((
+                                rotation.span(),
+                                format!("unknown rotation `{other}`, expected daily or hourly"),
+                            ));
+                        }
+                    };
+                    Ok(WriterConfig::TimeRotatingFile { path: path()?, rotation: parsed })
+                }
+                (None, None) => Ok(WriterConfig::File(path()?)),
+            },
+            other => Err((kind.span(), format!("unknown writer kind `{other}`, expected stderr, stdout or file"))),
+        }
+    }
+
+    /// The level directives, including the `[modules]` table
+    pub fn filter(&self) -> &Filter {
+        &self.filter
+    }
+
+    /// Create the logger, opening the files it writes to
+    pub fn build(&self) -> io::Result<ConfigLogger> {
+        let mut writers = Vec::new();
+        let options = FileOptions::default();
+        for writer in &self.writers {
+            writers.push(match writer {
+                WriterConfig::Stderr => Target::Stderr,
+                WriterConfig::Stdout => Target::Stdout,
+                WriterConfig::File(path) => Target::File(LogFileWriter::open(path, &options)?),
+                WriterConfig::RotatingFile { path, max_bytes, max_backups } => {
+                    Target::RotatingFile(RotatingFileWriter::open(path, *max_bytes, *max_backups, &options)?)
+                }
+                WriterConfig::TimeRotatingFile { path, rotation } => {
+                    Target::TimeRotatingFile(TimeRotatingFileWriter::open(path, *rotation, &options)?)
+                }
+            });
+        }
+        if writers.is_empty() {
+            writers.push(Target::Stderr);
+        }
+
+        let formatter: Box<dyn LogFormatter> = match &self.format {
+            Format::Default(formatter) => Box::new(*formatter),
+            Format::Json => Box::new(JsonFormatter),
+            Format::Logfmt => Box::new(LogfmtFormatter),
+            Format::Pattern(formatter) => Box::new(formatter.clone()),
+        };
+        Ok(BaseLogger::new(LevelFilter::Off, writers)
+            .with_filter(self.filter.clone())
+            .with_color(self.color)
+            .with_timestamp(self.timestamp.clone())
+            .with_formatter(formatter))
+    }
+
+    /// Build the logger and install it, replacing the active one
+    pub fn install(&self) -> io::Result<LoggerHandle> {
+        Ok(self.build()?.install())
+    }
+
+    /// Poll the file at `path` every `interval` and apply level changes through `handle`. Other settings only
+    /// take effect on restart, changing them logs a warning, and so does an invalid file, which leaves the levels
+    /// as they are. Watching stops when the returned ConfigWatcher is dropped
+    pub fn watch(
+        &self,
+        path: impl Into<PathBuf>,
+        handle: LoggerHandle,
+        interval: Duration,
+    ) -> io::Result<ConfigWatcher> {
+        let path = path.into();
+        let mut current = self.clone();
+        let (stop, stopped) = mpsc::channel();
+        let modified = |path: &Path| fs::metadata(path).and_then(|meta| Ok((meta.modified()?, meta.len()))).ok();
+        let mut last_modified: Option<(SystemTime, u64)> = modified(&path);
+
+        let thread = thread::Builder::new().name("rs_logger-config".into()).spawn(move || {
+            while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(interval) {
+                let now = modified(&path);
+                if now == last_modified {
+                    continue;
+                }
+                last_modified = now;
+
+                match Config::load(&path) {
+                    Ok(config) => {
+                        handle.set_filter(config.filter.clone());
+                        if !current.same_outputs(&config) {
+                            crate::log_print!(
+                                Level::Warn,
+                                "only levels are reloaded from {}, restart to apply the other changes",
+                                path.display()
+                            );
+                        }
+                        current = config;
+                    }
+                    Err(err) => {
+                        crate::log_print!(Level::Warn, "ignoring invalid {}: {err}", path.display());
+                    }
+                }
+            }
+        })?;
+        Ok(ConfigWatcher { stop, thread: Some(thread) })
+    }
+
+    /// Whether everything but the levels is the same
+    fn same_outputs(&self, other: &Config) -> bool {
+        self.format == other.format
+            && self.color == other.color
+            && self.timestamp == other.timestamp
+            && self.writers == other.writers
+    }
+}
+
+impl FromStr for Config {
+    type Err = ConfigError;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        Self::parse(source)
+    }
+}
+
+/// ConfigWatcher reloads levels from a config file until it is dropped, see [`Config::watch`]
+pub struct ConfigWatcher {
+    stop: Sender<()>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Drop for ConfigWatcher {
+    fn drop(&mut self) {
+        let _ = self.stop.send(());
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Logger built from a [`Config`]
+pub type ConfigLogger = BaseLogger<NopAppender, Vec<Target>, Box<dyn LogFormatter>>;
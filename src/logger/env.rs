@@ -0,0 +1,127 @@
+use std::env;
+
+use log::{Level, LevelFilter};
+
+use super::{appender::*, filter::*, formatter::*, handle::*, logger::*, timestamp::*, writer::*};
+
+impl<A> BaseLogger<A, Target>
+where
+    A: LogAppender,
+{
+    /// Install a logger configured from `RUST_LOG` and friends, see [`BaseLogger::from_env_var`]
+    pub fn init_from_env() -> LoggerHandle {
+        Self::init_from_env_var("RUST_LOG")
+    }
+
+    /// Install a logger configured from the environment variables starting with `name`, replacing the active one
+    pub fn init_from_env_var(name: &str) -> LoggerHandle {
+        Self::from_env_var(name).install()
+    }
+
+    /// Build a logger from the environment variables starting with `name`, so containers can be reconfigured
+    /// without code changes:
+    ///
+    /// - `NAME`: level directives such as `info,hyper=warn`, see [`Filter`]. Only errors are logged when unset
+    /// - `NAME_STYLE`: `auto`, `always` or `never`, see [`ColorMode`]
+    /// - `NAME_TIMESTAMP`: `iso`, `rfc3339`, `unix`, `unix_ms` or `none`
+    /// - `NAME_TIMEZONE`: `utc`, `local` or a zone such as `Asia/Shanghai`
+    /// - `NAME_TARGET`: `stderr`, `stdout` or the path of a file to append to
+    ///
+    /// Invalid values are reported through `log_print!` and ignored
+    pub fn from_env_var(name: &str) -> Self {
+        Self::from_vars(name, |key| env::var(key).ok())
+    }
+
+    /// `from_env_var` with the variables looked up through `lookup`
+    pub(crate) fn from_vars(name: &str, lookup: impl Fn(&str) -> Option<String>) -> Self {
+        let var = |suffix: &str| {
+            let key = format!("{name}{suffix}");
+            lookup(&key).map(|value| (key, value))
+        };
+
+        let target = match var("_TARGET") {
+            Some((key, value)) => parse_target(&value).unwrap_or_else(|err| {
+                crate::log_print!(Level::Warn, "ignoring {key}={value:?}: {err}");
+                Target::Stderr
+            }),
+            None => Target::Stderr,
+        };
+        let mut logger = Self::new(LevelFilter::Error, target);
+
+        if let Some((key, value)) = var("") {
+            match Filter::parse(&value) {
+                Ok(filter) => logger = logger.with_filter(filter),
+                Err(err) => {
+                    crate::log_print!(Level::Warn, "ignoring {key}: {err}");
+                }
+            }
+        }
+        if let Some((key, value)) = var("_STYLE") {
+            match parse_color_mode(&value) {
+                Some(mode) => logger = logger.with_color(mode),
+                None => {
+                    crate::log_print!(Level::Warn, "ignoring {key}={value:?}, expected auto, always or never");
+                }
+            }
+        }
+
+        let mut timestamp = Timestamp::default();
+        if let Some((key, value)) = var("_TIMESTAMP") {
+            match parse_timestamp(&value) {
+                Some(parsed) => timestamp = parsed,
+                None => {
+                    crate::log_print!(
+                        Level::Warn,
+                        "ignoring {key}={value:?}, expected iso, rfc3339, unix, unix_ms or none"
+                    );
+                }
+            }
+        }
+        if let Some((key, value)) = var("_TIMEZONE") {
+            match parse_time_zone(&value) {
+                Some(zone) => timestamp = timestamp.with_zone(zone),
+                None => {
+                    crate::log_print!(Level::Warn, "ignoring {key}={value:?}, unknown time zone");
+                }
+            }
+        }
+        logger.with_timestamp(timestamp)
+    }
+}
+
+pub(crate) fn parse_color_mode(value: &str) -> Option<ColorMode> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "auto" => Some(ColorMode::Auto),
+        "always" => Some(ColorMode::Always),
+        "never" => Some(ColorMode::Never),
+        _ => None,
+    }
+}
+
+pub(crate) fn parse_timestamp(value: &str) -> Option<Timestamp> {
+    let format = match value.trim().to_ascii_lowercase().as_str() {
+        "none" | "off" => return Some(Timestamp::none()),
+        "iso" => TimestampFormat::Iso,
+        "rfc3339" => TimestampFormat::Rfc3339,
+        "unix" => TimestampFormat::UnixSeconds,
+        "unix_ms" => TimestampFormat::UnixMillis,
+        _ => return None,
+    };
+    Some(Timestamp::new(format))
+}
+
+pub(crate) fn parse_time_zone(value: &str) -> Option<TimeZone> {
+    match value.trim() {
+        zone if zone.eq_ignore_ascii_case("utc") => Some(TimeZone::utc()),
+        zone if zone.eq_ignore_ascii_case("local") => Some(TimeZone::local()),
+        zone => TimeZone::named(zone),
+    }
+}
+
+pub(crate) fn parse_target(value: &str) -> std::io::Result<Target> {
+    match value.trim() {
+        target if target.eq_ignore_ascii_case("stderr") => Ok(Target::Stderr),
+        target if target.eq_ignore_ascii_case("stdout") => Ok(Target::Stdout),
+        path => Ok(Target::File(LogFileWriter::open(path, &FileOptions::default())?)),
+    }
+}
@@ -0,0 +1,120 @@
+use std::{error::Error, fmt};
+
+use log::SetLoggerError;
+
+/// InitError is returned by the `try_init*` functions when the logger could not be installed
+#[derive(Debug)]
+pub enum InitError {
+    /// Another logger has already been installed through the `log` crate
+    SetLogger(SetLoggerError),
+    /// A logger from this crate has already been initialized
+    AlreadyInitialized,
+}
+
+impl fmt::Display for InitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InitError::SetLogger(err) => write!(f, "another logger is already installed: {err}"),
+            InitError::AlreadyInitialized => write!(f, "rs_logger is already initialized"),
+        }
+    }
+}
+
+impl Error for InitError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            InitError::SetLogger(err) => Some(err),
+            InitError::AlreadyInitialized => None,
+        }
+    }
+}
+
+impl From<SetLoggerError> for InitError {
+    fn from(err: SetLoggerError) -> Self {
+        InitError::SetLogger(err)
+    }
+}
+
+/// PatternError is returned when a layout pattern string can't be parsed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatternError {
+    /// Byte offset of the offending `{` in the pattern
+    pub position: usize,
+    pub message: String,
+}
+
+impl PatternError {
+    pub(crate) fn new(position: usize, message: impl Into<String>) -> Self {
+        Self { position, message: message.into() }
+    }
+}
+
+impl fmt::Display for PatternError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid pattern at byte {}: {}", self.position, self.message)
+    }
+}
+
+impl Error for PatternError {}
+
+/// FilterError is returned when a level directive string can't be parsed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilterError {
+    /// The offending directive, such as `hyper=loud`
+    pub directive: String,
+    pub message: String,
+}
+
+impl FilterError {
+    pub(crate) fn new(directive: &str, message: impl Into<String>) -> Self {
+        Self { directive: directive.to_string(), message: message.into() }
+    }
+}
+
+impl fmt::Display for FilterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid directive `{}`: {}", self.directive, self.message)
+    }
+}
+
+impl Error for FilterError {}
+
+/// ConfigError is returned when a configuration file can't be read or is invalid
+#[cfg(feature = "config")]
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(std::io::Error),
+    /// The file isn't valid TOML or has a setting that doesn't make sense, lines and columns start at 1
+    Invalid {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+}
+
+#[cfg(feature = "config")]
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(err) => write!(f, "failed to read config: {err}"),
+            ConfigError::Invalid { line, column, message } => write!(f, "line {line}, column {column}: {message}"),
+        }
+    }
+}
+
+#[cfg(feature = "config")]
+impl Error for ConfigError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ConfigError::Io(err) => Some(err),
+            ConfigError::Invalid { .. } => None,
+        }
+    }
+}
+
+#[cfg(feature = "config")]
+impl From<std::io::Error> for ConfigError {
+    fn from(err: std::io::Error) -> Self {
+        ConfigError::Io(err)
+    }
+}
@@ -0,0 +1,99 @@
+use std::str::FromStr;
+
+use log::{LevelFilter, Metadata};
+
+use super::error::*;
+
+/// Filter decides the level per module from directives such as `info,my_crate::db=trace,hyper=warn`, the same
+/// syntax as `RUST_LOG`. A directive applies to a module and everything below it, and the longest matching
+/// module wins. A bare level sets the level of modules without a directive, which are off otherwise, and a
+/// bare module enables all of its levels. Like `RUST_LOG`, an empty string enables errors only
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Filter {
+    default: LevelFilter,
+    /// Sorted by module length, longest first, so the first match is the most specific
+    directives: Vec<(String, LevelFilter)>,
+}
+
+impl Filter {
+    /// A filter that applies `level` to every module
+    pub fn new(level: LevelFilter) -> Self {
+        Self { default: level, directives: Vec::new() }
+    }
+
+    /// Parse a comma separated list of `level`, `module` and `module=level` directives
+    pub fn parse(directives: &str) -> Result<Self, FilterError> {
+        let mut filter = Self::new(LevelFilter::Off);
+        let mut default = None;
+        for directive in directives.split(',').map(str::trim).filter(|directive| !directive.is_empty()) {
+            let parse_level = |level: &str| {
+                level.trim().parse::<LevelFilter>().map_err(|_| FilterError::new(directive, "unknown level"))
+            };
+            match directive.split_once('=') {
+                Some((module, _)) if module.trim().is_empty() => {
+                    return Err(FilterError::new(directive, "missing module name"));
+                }
+                Some((module, level)) if is_module_path(module.trim()) => {
+                    filter = filter.with_directive(module.trim(), parse_level(level)?);
+                }
+                Some(_) => return Err(FilterError::new(directive, "invalid module path")),
+                None => match directive.parse::<LevelFilter>() {
+                    Ok(level) => default = Some(level),
+                    Err(_) if is_module_path(directive) => {
+                        filter = filter.with_directive(directive, LevelFilter::Trace)
+                    }
+                    Err(_) => return Err(FilterError::new(directive, "neither a level nor a module path")),
+                },
+            }
+        }
+        filter.default =
+            default.unwrap_or(if filter.directives.is_empty() { LevelFilter::Error } else { LevelFilter::Off });
+        Ok(filter)
+    }
+
+    /// Set the level of `module` and its submodules, replacing an earlier directive for the same module
+    pub fn with_directive(mut self, module: &str, level: LevelFilter) -> Self {
+        self.directives.retain(|(existing, _)| existing != module);
+        let index = self.directives.partition_point(|(existing, _)| existing.len() >= module.len());
+        self.directives.insert(index, (module.to_string(), level));
+        self
+    }
+
+    /// Level of records whose target is `target`
+    pub fn level(&self, target: &str) -> LevelFilter {
+        self.directives
+            .iter()
+            .find(|(module, _)| {
+                target.strip_prefix(module.as_str()).is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+            })
+            .map_or(self.default, |&(_, level)| level)
+    }
+
+    pub fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level(metadata.target())
+    }
+
+    /// The most verbose level of any directive, what `log::set_max_level` should be set to
+    pub fn max_level(&self) -> LevelFilter {
+        self.directives.iter().map(|&(_, level)| level).fold(self.default, Ord::max)
+    }
+}
+
+impl From<LevelFilter> for Filter {
+    fn from(level: LevelFilter) -> Self {
+        Self::new(level)
+    }
+}
+
+impl FromStr for Filter {
+    type Err = FilterError;
+
+    fn from_str(directives: &str) -> Result<Self, Self::Err> {
+        Self::parse(directives)
+    }
+}
+
+pub(crate) fn is_module_path(path: &str) -> bool {
+    path.split("::")
+        .all(|segment| !segment.is_empty() && segment.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '-'))
+}
@@ -0,0 +1,194 @@
+use std::{env, io, io::Write, time::Duration};
+
+use log::{
+    Record,
+    kv::{self, Key, Value, VisitSource},
+};
+
+use super::{theme::*, writer::LogWriter};
+
+/// FormatContext carries everything BaseLogger has prepared for a record besides the record itself
+pub struct FormatContext<'a> {
+    /// Time of the record since the Unix epoch
+    pub timestamp: Duration,
+    /// `timestamp` formatted by the logger, `None` if the logger omits timestamps
+    pub time: Option<&'a str>,
+    /// Offset from UTC of the logger's time zone at `timestamp`, in seconds
+    pub utc_offset: i32,
+    /// Output of the LogAppender, `None` if it didn't append anything
+    pub extra: Option<&'a str>,
+    /// Styles to color the line with, `None` when the logger's [`ColorMode`] resolved to no colors
+    pub theme: Option<&'a Theme>,
+}
+
+/// LogFormatter writes one complete log line for a record, including the trailing newline
+pub trait LogFormatter: Send + Sync + 'static {
+    fn format(&self, out: &mut dyn Write, record: &Record, ctx: &FormatContext) -> io::Result<()>;
+}
+
+impl LogFormatter for Box<dyn LogFormatter> {
+    fn format(&self, out: &mut dyn Write, record: &Record, ctx: &FormatContext) -> io::Result<()> {
+        (**self).format(out, record, ctx)
+    }
+}
+
+/// ColorMode decides whether log levels are coloured
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorMode {
+    /// Colour when the writer is a terminal. `NO_COLOR` turns colours off and `FORCE_COLOR` turns them on for
+    /// writers that aren't files, see <https://no-color.org> and <https://force-color.org>
+    Auto,
+    Always,
+    Never,
+}
+
+impl Default for ColorMode {
+    /// `Auto` with the `log_level_color` feature, `Never` otherwise
+    fn default() -> Self {
+        if cfg!(feature = "log_level_color") { ColorMode::Auto } else { ColorMode::Never }
+    }
+}
+
+impl ColorMode {
+    /// Whether to colour output going to a writer that `is_terminal` or not
+    pub fn resolve(self, is_terminal: bool) -> bool {
+        let set = |name| env::var_os(name).is_some_and(|value| !value.is_empty());
+        match self {
+            ColorMode::Always => true,
+            ColorMode::Never => false,
+            ColorMode::Auto => !set("NO_COLOR") && (set("FORCE_COLOR") || is_terminal),
+        }
+    }
+
+    /// Whether to colour output going to `writer`, files are only coloured with `Always`
+    pub fn resolve_for<W: LogWriter>(self, writer: &W) -> bool {
+        if writer.is_file() { self == ColorMode::Always } else { self.resolve(writer.is_terminal()) }
+    }
+}
+
+/// SourceLocation controls whether log lines show the file and line of the call site
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SourceLocation {
+    Off,
+    /// The path as recorded by the compiler, e.g. `/home/me/.cargo/registry/src/.../hyper-1.0.0/src/client.rs:42`
+    Full,
+    /// The path relative to the crate, e.g. `src/client.rs:42`
+    Short,
+}
+
+impl Default for SourceLocation {
+    /// `Short` with the `source_location` feature, `Off` otherwise
+    fn default() -> Self {
+        if cfg!(feature = "source_location") { SourceLocation::Short } else { SourceLocation::Off }
+    }
+}
+
+impl SourceLocation {
+    /// `file:line` of the record, `None` when disabled or when the record has no file
+    pub fn format(&self, record: &Record) -> Option<String> {
+        let file = match self {
+            SourceLocation::Off => return None,
+            SourceLocation::Full => record.file()?,
+            SourceLocation::Short => short_path(record.file()?),
+        };
+        Some(match record.line() {
+            Some(line) => format!("{file}:{line}"),
+            None => file.to_string(),
+        })
+    }
+}
+
+/// Strips everything before the last `src` directory, which turns both absolute paths of dependencies and
+/// workspace-relative paths into crate-relative ones
+fn short_path(file: &str) -> &str {
+    let bytes = file.as_bytes();
+    file.rmatch_indices("src")
+        .find(|&(i, _)| {
+            (i == 0 || matches!(bytes[i - 1], b'/' | b'\\')) && matches!(bytes.get(i + 3), Some(b'/' | b'\\'))
+        })
+        .map_or(file, |(i, _)| &file[i..])
+}
+
+/// DefaultFormatter writes `[time level module] - message`, or `[time level module] extra - message`
+/// when the appender added something. Without a timestamp the line starts with `[level module]`. Record
+/// key-values follow the message as `key=value`, and the source location follows the module when enabled:
+/// `[time level module src/db.rs:42] - message`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DefaultFormatter {
+    source_location: SourceLocation,
+}
+
+impl DefaultFormatter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_source_location(mut self, source_location: SourceLocation) -> Self {
+        self.source_location = source_location;
+        self
+    }
+}
+
+impl LogFormatter for DefaultFormatter {
+    fn format(&self, out: &mut dyn Write, record: &Record, ctx: &FormatContext) -> io::Result<()> {
+        static PLAIN: Style = Style::new();
+        let level = record.level();
+        let (time_style, level_style, module_style, message_style) = match ctx.theme {
+            Some(theme) => (theme.timestamp(), theme.level(level), theme.module(), theme.message(level)),
+            None => (&PLAIN, &PLAIN, &PLAIN, &PLAIN),
+        };
+
+        let module = record.module_path().unwrap_or("unknown");
+        if let Some(time) = ctx.time {
+            write!(out, "[{} ", time_style.paint(time))?;
+        } else {
+            write!(out, "[")?;
+        }
+        write!(out, "{} {}", level_style.paint(level), module_style.paint(module))?;
+        if let Some(location) = self.source_location.format(record) {
+            write!(out, " {location}")?;
+        }
+        write!(out, "] ")?;
+        if let Some(extra) = ctx.extra {
+            write!(out, "{extra} ")?;
+        }
+        write!(out, "- ")?;
+        if message_style.is_plain() {
+            write!(out, "{}", record.args())?;
+            write_key_values(out, record)?;
+        } else {
+            let mut message = Vec::new();
+            write!(message, "{}", record.args())?;
+            write_key_values(&mut message, record)?;
+            write!(out, "{}", message_style.paint(String::from_utf8_lossy(&message)))?;
+        }
+        writeln!(out)
+    }
+}
+
+/// Calls `f` with every key-value of `record`, stopping at the first error
+pub(crate) fn visit_key_values(record: &Record, f: impl FnMut(Key, Value) -> io::Result<()>) -> io::Result<()> {
+    struct Visitor<F> {
+        f: F,
+        result: io::Result<()>,
+    }
+
+    impl<'kvs, F: FnMut(Key, Value) -> io::Result<()>> VisitSource<'kvs> for Visitor<F> {
+        fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), kv::Error> {
+            if let Err(err) = (self.f)(key, value) {
+                self.result = Err(err);
+                return Err(kv::Error::msg("failed to write key-value"));
+            }
+            Ok(())
+        }
+    }
+
+    let mut visitor = Visitor { f, result: Ok(()) };
+    let _ = record.key_values().visit(&mut visitor);
+    visitor.result
+}
+
+/// Writes the key-values of `record` as ` key=value` pairs
+pub(crate) fn write_key_values(out: &mut dyn Write, record: &Record) -> io::Result<()> {
+    visit_key_values(record, |key, value| write!(out, " {key}={value}"))
+}
@@ -0,0 +1,78 @@
+use std::sync::{Mutex, RwLock};
+
+use log::{LevelFilter, Log, Metadata, Record};
+
+use super::error::*;
+
+/// GlobalLogger is the logger this crate installs into `log`. It forwards every record to the active logger,
+/// which can be replaced at any time, so the writer, appender and level can all be changed after init
+pub struct GlobalLogger {
+    active: RwLock<Option<Box<dyn Log>>>,
+}
+
+static GLOBAL: GlobalLogger = GlobalLogger { active: RwLock::new(None) };
+
+impl GlobalLogger {
+    /// Install GlobalLogger into `log`, this only fails if a logger from another crate is already installed
+    fn register() -> Result<(), InitError> {
+        static REGISTERED: Mutex<bool> = Mutex::new(false);
+        let mut registered = REGISTERED.lock().unwrap();
+        if !*registered {
+            log::set_logger(&GLOBAL)?;
+            *registered = true;
+        }
+        Ok(())
+    }
+
+    fn set(logger: Box<dyn Log>, level: LevelFilter, replace: bool) -> Result<(), InitError> {
+        Self::register()?;
+        let mut active = GLOBAL.active.write().unwrap();
+        if active.is_some() && !replace {
+            return Err(InitError::AlreadyInitialized);
+        }
+        *active = Some(logger);
+        log::set_max_level(level);
+        Ok(())
+    }
+
+    /// Make `logger` the active logger, replacing the current one. `level` becomes `log`'s max level
+    pub fn replace(logger: impl Log + 'static, level: LevelFilter) -> Result<(), InitError> {
+        Self::set(Box::new(logger), level, true)
+    }
+
+    /// Make `logger` the active logger, unless there already is one
+    pub fn try_set(logger: impl Log + 'static, level: LevelFilter) -> Result<(), InitError> {
+        Self::set(Box::new(logger), level, false)
+    }
+
+    /// Remove the active logger, records are discarded until a new one is set
+    pub fn reset() {
+        let mut active = GLOBAL.active.write().unwrap();
+        if let Some(logger) = active.take() {
+            logger.flush();
+        }
+        log::set_max_level(LevelFilter::Off);
+    }
+
+    pub fn is_initialized() -> bool {
+        GLOBAL.active.read().unwrap().is_some()
+    }
+}
+
+impl Log for GlobalLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.active.read().unwrap().as_ref().is_some_and(|logger| logger.enabled(metadata))
+    }
+
+    fn log(&self, record: &Record) {
+        if let Some(logger) = self.active.read().unwrap().as_ref() {
+            logger.log(record);
+        }
+    }
+
+    fn flush(&self) {
+        if let Some(logger) = self.active.read().unwrap().as_ref() {
+            logger.flush();
+        }
+    }
+}
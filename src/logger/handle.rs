@@ -0,0 +1,118 @@
+use std::{
+    ops::Deref,
+    sync::{
+        Arc, RwLock, Weak,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+
+use log::{LevelFilter, Metadata};
+
+use super::filter::*;
+
+/// SharedFilter is the Filter of a BaseLogger, shared with its LoggerHandles
+#[derive(Debug)]
+pub(crate) struct SharedFilter {
+    filter: RwLock<Filter>,
+    /// Whether the logger was installed, only then does a change of filter update `log::max_level`
+    installed: AtomicBool,
+}
+
+impl SharedFilter {
+    pub(crate) fn new(filter: Filter) -> Self {
+        Self { filter: RwLock::new(filter), installed: AtomicBool::new(false) }
+    }
+
+    pub(crate) fn enabled(&self, metadata: &Metadata) -> bool {
+        self.filter.read().unwrap().enabled(metadata)
+    }
+
+    pub(crate) fn max_level(&self) -> LevelFilter {
+        self.filter.read().unwrap().max_level()
+    }
+
+    pub(crate) fn set_installed(&self) {
+        self.installed.store(true, Ordering::SeqCst);
+    }
+
+    /// Taken under the write lock, so a handle that is changing the filter right now either finishes before
+    /// the logger is gone or sees it gone
+    fn set_uninstalled(&self) {
+        let _filter = self.filter.write().unwrap();
+        self.installed.store(false, Ordering::SeqCst);
+    }
+
+    /// Change the filter, the write lock is held until `log::max_level` matches, so concurrent changes can't
+    /// leave it out of step with the filter
+    pub(crate) fn update(&self, f: impl FnOnce(&mut Filter)) {
+        let mut filter = self.filter.write().unwrap();
+        f(&mut filter);
+        if self.installed.load(Ordering::SeqCst) {
+            log::set_max_level(filter.max_level());
+        }
+    }
+}
+
+/// FilterOwner is the BaseLogger's reference to its SharedFilter. Dropping it, which happens when the logger is
+/// replaced, stops handles that are still holding the filter from changing `log::max_level`
+#[derive(Debug)]
+pub(crate) struct FilterOwner(Arc<SharedFilter>);
+
+impl FilterOwner {
+    pub(crate) fn new(filter: Filter) -> Self {
+        Self(Arc::new(SharedFilter::new(filter)))
+    }
+}
+
+impl Deref for FilterOwner {
+    type Target = Arc<SharedFilter>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Drop for FilterOwner {
+    fn drop(&mut self) {
+        self.0.set_uninstalled();
+    }
+}
+
+/// LoggerHandle changes the levels of a logger at runtime, for example from an admin endpoint. It is returned by
+/// `init` and `install`, clones control the same logger, and once the logger has been replaced the handle does
+/// nothing
+#[derive(Clone, Debug)]
+pub struct LoggerHandle {
+    shared: Weak<SharedFilter>,
+}
+
+impl LoggerHandle {
+    pub(crate) fn new(shared: &Arc<SharedFilter>) -> Self {
+        Self { shared: Arc::downgrade(shared) }
+    }
+
+    /// Apply `level` to every module, dropping all module directives
+    pub fn set_level(&self, level: LevelFilter) {
+        self.set_filter(Filter::new(level));
+    }
+
+    /// Set the level of `module` and its submodules, keeping the other directives
+    pub fn set_directive(&self, module: &str, level: LevelFilter) {
+        self.update(|filter| *filter = filter.clone().with_directive(module, level));
+    }
+
+    pub fn set_filter(&self, new: Filter) {
+        self.update(|filter| *filter = new);
+    }
+
+    /// The current filter, `None` once the logger has been replaced. Keep it to restore the levels later
+    pub fn filter(&self) -> Option<Filter> {
+        Some(self.shared.upgrade()?.filter.read().unwrap().clone())
+    }
+
+    fn update(&self, f: impl FnOnce(&mut Filter)) {
+        if let Some(shared) = self.shared.upgrade() {
+            shared.update(f);
+        }
+    }
+}
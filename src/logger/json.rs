@@ -0,0 +1,123 @@
+use std::{fmt, io, io::Write};
+
+use log::{Record, kv::Value};
+
+use super::formatter::*;
+
+/// JsonFormatter writes one JSON object per line:
+/// `{"ts":"...","level":"INFO","target":"...","module":"...","file":"...","line":1,"msg":"...","extra":"..."}`.
+/// Levels are never colored, `extra` holds the LogAppender output when there is any, and record key-values
+/// go in a `fields` object, so a key such as `level` can't shadow the record's own
+#[derive(Clone, Copy, Debug, Default)]
+pub struct JsonFormatter;
+
+impl LogFormatter for JsonFormatter {
+    fn format(&self, out: &mut dyn Write, record: &Record, ctx: &FormatContext) -> io::Result<()> {
+        write!(out, "{{")?;
+        if let Some(time) = ctx.time {
+            write!(out, "\"ts\":")?;
+            write_json_str(out, time)?;
+            write!(out, ",")?;
+        }
+        write!(out, "\"level\":\"{}\",\"target\":", record.level())?;
+        write_json_str(out, record.target())?;
+        write!(out, ",\"module\":")?;
+        write_json_opt_str(out, record.module_path())?;
+        write!(out, ",\"file\":")?;
+        write_json_opt_str(out, record.file())?;
+        match record.line() {
+            Some(line) => write!(out, ",\"line\":{line}")?,
+            None => write!(out, ",\"line\":null")?,
+        }
+        write!(out, ",\"msg\":")?;
+        write_json_str(out, record.args())?;
+        if let Some(extra) = ctx.extra {
+            write!(out, ",\"extra\":")?;
+            write_json_str(out, extra)?;
+        }
+        if record.key_values().count() > 0 {
+            write!(out, ",\"fields\":{{")?;
+            let mut first = true;
+            visit_key_values(record, |key, value| {
+                if !std::mem::take(&mut first) {
+                    write!(out, ",")?;
+                }
+                write_json_str(out, key)?;
+                write!(out, ":")?;
+                write_json_value(out, &value)
+            })?;
+            write!(out, "}}")?;
+        }
+        writeln!(out, "}}")
+    }
+}
+
+fn write_json_opt_str(out: &mut dyn Write, value: Option<&str>) -> io::Result<()> {
+    match value {
+        Some(value) => write_json_str(out, value),
+        None => write!(out, "null"),
+    }
+}
+
+/// Writes booleans and numbers as JSON literals, everything else as a string
+fn write_json_value(out: &mut dyn Write, value: &Value) -> io::Result<()> {
+    if let Some(value) = value.to_bool() {
+        write!(out, "{value}")
+    } else if let Some(value) = value.to_i64() {
+        write!(out, "{value}")
+    } else if let Some(value) = value.to_u64() {
+        write!(out, "{value}")
+    } else if let Some(value) = value.to_f64().filter(|value| value.is_finite()) {
+        write!(out, "{value}")
+    } else {
+        write_json_str(out, value)
+    }
+}
+
+/// Writes `value` as a quoted JSON string
+pub(crate) fn write_json_str(out: &mut dyn Write, value: impl fmt::Display) -> io::Result<()> {
+    out.write_all(b"\"")?;
+    let mut escaper = JsonEscaper { out, result: Ok(()) };
+    if fmt::write(&mut escaper, format_args!("{value}")).is_err() {
+        return escaper.result;
+    }
+    out.write_all(b"\"")
+}
+
+/// JsonEscaper escapes formatted text on its way to the output, so messages never have to be collected first
+struct JsonEscaper<'a> {
+    out: &'a mut dyn Write,
+    result: io::Result<()>,
+}
+
+impl JsonEscaper<'_> {
+    fn escape(&mut self, s: &str) -> io::Result<()> {
+        let bytes = s.as_bytes();
+        let mut start = 0;
+        for (i, c) in s.char_indices() {
+            if !matches!(c, '"' | '\\') && !c.is_control() {
+                continue;
+            }
+            self.out.write_all(&bytes[start..i])?;
+            match c {
+                '"' => self.out.write_all(b"\\\"")?,
+                '\\' => self.out.write_all(b"\\\\")?,
+                '\n' => self.out.write_all(b"\\n")?,
+                '\r' => self.out.write_all(b"\\r")?,
+                '\t' => self.out.write_all(b"\\t")?,
+                c => write!(self.out, "\\u{:04x}", c as u32)?,
+            }
+            start = i + c.len_utf8();
+        }
+        self.out.write_all(&bytes[start..])
+    }
+}
+
+impl fmt::Write for JsonEscaper<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.escape(s).map_err(|err| {
+            self.result = Err(err);
+            fmt::Error
+        })
+    }
+}
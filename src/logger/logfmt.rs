@@ -0,0 +1,80 @@
+use std::{fmt, io, io::Write};
+
+use log::Record;
+
+use super::formatter::*;
+
+/// LogfmtFormatter writes records in logfmt, `ts=... level=info module=app::db msg="user login" user_id=5`,
+/// followed by the record's key-values. Values containing spaces, quotes or `=` are quoted. Key-values named
+/// like a built-in key are written as `fields.<key>`, and characters keys can't hold become `_`
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LogfmtFormatter;
+
+impl LogFormatter for LogfmtFormatter {
+    fn format(&self, out: &mut dyn Write, record: &Record, ctx: &FormatContext) -> io::Result<()> {
+        if let Some(time) = ctx.time {
+            write!(out, "ts=")?;
+            write_logfmt_value(out, time)?;
+            write!(out, " ")?;
+        }
+        write!(out, "level={}", record.level().as_str().to_ascii_lowercase())?;
+        if let Some(module) = record.module_path() {
+            write!(out, " module=")?;
+            write_logfmt_value(out, module)?;
+        }
+        if let Some(extra) = ctx.extra {
+            write!(out, " extra=")?;
+            write_logfmt_value(out, extra)?;
+        }
+        write!(out, " msg=")?;
+        write_logfmt_value(out, record.args())?;
+
+        visit_key_values(record, |key, value| {
+            write!(out, " ")?;
+            write_logfmt_key(out, key.as_str())?;
+            write!(out, "=")?;
+            write_logfmt_value(out, value)
+        })?;
+        writeln!(out)
+    }
+}
+
+/// Writes a record key, prefixed when it collides with a built-in key
+fn write_logfmt_key(out: &mut dyn Write, key: &str) -> io::Result<()> {
+    if matches!(key, "ts" | "level" | "module" | "extra" | "msg") {
+        write!(out, "fields.")?;
+    }
+    if key.is_empty() {
+        return write!(out, "_");
+    }
+    for c in key.chars() {
+        let c = if c == ' ' || c == '"' || c == '=' || c.is_control() { '_' } else { c };
+        write!(out, "{c}")?;
+    }
+    Ok(())
+}
+
+/// Writes `value` bare when possible, quoted and escaped when it is empty or contains spaces, quotes,
+/// `=` or control characters
+pub(crate) fn write_logfmt_value(out: &mut dyn Write, value: impl fmt::Display) -> io::Result<()> {
+    let value = value.to_string();
+    let needs_quotes =
+        value.is_empty() || value.chars().any(|c| c == ' ' || c == '"' || c == '=' || c == '\\' || c.is_control());
+    if !needs_quotes {
+        return out.write_all(value.as_bytes());
+    }
+
+    out.write_all(b"\"")?;
+    for c in value.chars() {
+        match c {
+            '"' => out.write_all(b"\\\"")?,
+            '\\' => out.write_all(b"\\\\")?,
+            '\n' => out.write_all(b"\\n")?,
+            '\r' => out.write_all(b"\\r")?,
+            '\t' => out.write_all(b"\\t")?,
+            c if c.is_control() => write!(out, "\\u{:04x}", c as u32)?,
+            c => write!(out, "{c}")?,
+        }
+    }
+    out.write_all(b"\"")
+}
@@ -1,23 +1,26 @@
 use std::{
     fs::File,
     io,
-    io::{BufWriter, Write},
+    io::{IsTerminal, Write},
     marker::PhantomData,
-    sync::Once,
+    path::Path,
 };
 
 use log::{Level, LevelFilter, Log, Metadata, Record};
-use utc_dt::{
-    UTCDatetime,
-    time::{UTCTimestamp, UTCTransformations},
-};
 
-use super::{appender::*, writer::*};
+use super::{
+    appender::*, clock::*, error::*, filter::*, formatter::*, global::*, handle::*, theme::*, timestamp::*, writer::*,
+};
 
 /// Base Logger
-pub struct BaseLogger<A: LogAppender, W: LogWriter = Stderr> {
-    level: LevelFilter,
+pub struct BaseLogger<A: LogAppender, W: LogWriter = Stderr, F: LogFormatter = DefaultFormatter> {
+    filter: FilterOwner,
     writer: W,
+    formatter: F,
+    timestamp: Timestamp,
+    clock: Box<dyn Clock>,
+    color: bool,
+    theme: Theme,
     _appender: PhantomData<A>,
 }
 
@@ -25,8 +28,12 @@ impl<A> BaseLogger<A, Stderr>
 where
     A: LogAppender,
 {
-    pub fn init(level: LevelFilter) {
-        Self::init_with_writer(level, Stderr {});
+    pub fn init(level: LevelFilter) -> LoggerHandle {
+        Self::init_with_writer(level, Stderr {})
+    }
+
+    pub fn try_init(level: LevelFilter) -> Result<LoggerHandle, InitError> {
+        Self::try_init_with_writer(level, Stderr {})
     }
 }
 
@@ -34,8 +41,25 @@ impl<A> BaseLogger<A, Stdout>
 where
     A: LogAppender,
 {
-    pub fn init(level: LevelFilter) {
-        Self::init_with_writer(level, Stdout {});
+    pub fn init(level: LevelFilter) -> LoggerHandle {
+        Self::init_with_writer(level, Stdout {})
+    }
+
+    pub fn try_init(level: LevelFilter) -> Result<LoggerHandle, InitError> {
+        Self::try_init_with_writer(level, Stdout {})
+    }
+}
+
+impl<A> BaseLogger<A, SplitWriter>
+where
+    A: LogAppender,
+{
+    pub fn init(level: LevelFilter) -> LoggerHandle {
+        Self::init_with_writer(level, SplitWriter::new())
+    }
+
+    pub fn try_init(level: LevelFilter) -> Result<LoggerHandle, InitError> {
+        Self::try_init_with_writer(level, SplitWriter::new())
     }
 }
 
@@ -43,8 +67,25 @@ impl<A> BaseLogger<A, LogFileWriter>
 where
     A: LogAppender,
 {
-    pub fn init(level: LevelFilter, file: File) {
-        Self::init_with_writer(level, LogFileWriter::new(file));
+    pub fn init(level: LevelFilter, file: File) -> LoggerHandle {
+        Self::init_with_writer(level, LogFileWriter::new(file))
+    }
+
+    pub fn try_init(level: LevelFilter, file: File) -> Result<LoggerHandle, InitError> {
+        Self::try_init_with_writer(level, LogFileWriter::new(file))
+    }
+
+    /// Log to `path`, see [`LogFileWriter::open`]
+    pub fn init_path(level: LevelFilter, path: impl AsRef<Path>) -> io::Result<LoggerHandle> {
+        Self::init_path_with(level, path, &FileOptions::default())
+    }
+
+    pub fn init_path_with(
+        level: LevelFilter,
+        path: impl AsRef<Path>,
+        options: &FileOptions,
+    ) -> io::Result<LoggerHandle> {
+        Ok(Self::init_with_writer(level, LogFileWriter::open(path, options)?))
     }
 }
 
@@ -53,66 +94,189 @@ where
     A: LogAppender,
     W: LogWriter,
 {
-    pub fn init_with_writer(level: LevelFilter, writer: W) {
-        static INIT_ONCE: Once = Once::new();
-        INIT_ONCE.call_once(|| {
-            let logger = Self { level, writer, _appender: PhantomData };
-            log::set_boxed_logger(Box::new(logger)).unwrap();
-            log::set_max_level(level);
-        })
+    pub fn new(level: LevelFilter, writer: W) -> Self {
+        let color = ColorMode::default().resolve_for(&writer);
+        Self {
+            filter: FilterOwner::new(Filter::new(level)),
+            writer,
+            formatter: DefaultFormatter::default(),
+            timestamp: Timestamp::default(),
+            clock: Box::new(SystemClock),
+            color,
+            theme: Theme::default(),
+            _appender: PhantomData,
+        }
     }
 
-    fn now() -> String {
-        UTCDatetime::from_timestamp(UTCTimestamp::try_from_system_time().unwrap()).as_iso_datetime(3)
+    /// Install the logger, replacing the active one if any. Panics if a logger from another crate is already installed
+    pub fn init_with_writer(level: LevelFilter, writer: W) -> LoggerHandle {
+        Self::new(level, writer).install()
+    }
+
+    /// Install the logger, or report why it could not be installed. Unlike `init_with_writer`, this never
+    /// replaces an active logger
+    pub fn try_init_with_writer(level: LevelFilter, writer: W) -> Result<LoggerHandle, InitError> {
+        Self::new(level, writer).try_install()
     }
 
     /// Print log directly, can be used before the logging framework is initialized
     pub fn print(level: Level, module: &str, message: &str) {
-        let stream = io::stderr();
-        let mut stream = stream.lock();
-        let _ = writeln!(stream, "[{} {} {}] - {}", Self::now(), Self::styled_level(level), module, message);
+        Self::print_record(
+            &Record::builder()
+                .level(level)
+                .target(module)
+                .module_path(Some(module))
+                .args(format_args!("{message}"))
+                .build(),
+        );
+    }
+
+    /// Like `print`, with the call site shown when source locations are enabled, used by `log_print!`
+    pub fn print_at(level: Level, module: &str, file: &str, line: u32, message: &str) {
+        Self::print_record(
+            &Record::builder()
+                .level(level)
+                .target(module)
+                .module_path(Some(module))
+                .file(Some(file))
+                .line(Some(line))
+                .args(format_args!("{message}"))
+                .build(),
+        );
+    }
+
+    fn print_record(record: &Record) {
+        let timestamp = SystemClock.now();
+        let time = Timestamp::default().render(timestamp);
+        let color = ColorMode::default().resolve(io::stderr().is_terminal());
+        let theme = Theme::default();
+        let ctx = FormatContext {
+            timestamp,
+            time: time.as_deref(),
+            utc_offset: 0,
+            extra: None,
+            theme: color.then_some(&theme),
+        };
+        let mut line = Vec::with_capacity(256);
+        let _ = DefaultFormatter::default().format(&mut line, record, &ctx);
+        let mut stream = io::stderr().lock();
+        let _ = stream.write_all(&line);
         let _ = stream.flush();
     }
+}
+
+impl<A, W, F> BaseLogger<A, W, F>
+where
+    A: LogAppender,
+    W: LogWriter,
+    F: LogFormatter,
+{
+    /// Replace the line layout, see [`LogFormatter`]
+    pub fn with_formatter<F2: LogFormatter>(self, formatter: F2) -> BaseLogger<A, W, F2> {
+        BaseLogger {
+            filter: self.filter,
+            writer: self.writer,
+            formatter,
+            timestamp: self.timestamp,
+            clock: self.clock,
+            color: self.color,
+            theme: self.theme,
+            _appender: PhantomData,
+        }
+    }
+
+    /// Replace the timestamp policy, see [`Timestamp`]
+    pub fn with_timestamp(mut self, timestamp: Timestamp) -> Self {
+        self.timestamp = timestamp;
+        self
+    }
+
+    /// Replace the source of record times, see [`Clock`]
+    pub fn with_clock(mut self, clock: impl Clock) -> Self {
+        self.clock = Box::new(clock);
+        self
+    }
+
+    /// Replace the level with per-module directives, see [`Filter`]
+    pub fn with_filter(self, filter: Filter) -> Self {
+        self.filter.update(|current| *current = filter);
+        self
+    }
+
+    /// A handle to change the levels of this logger after it has been installed, see [`LoggerHandle`]
+    pub fn handle(&self) -> LoggerHandle {
+        LoggerHandle::new(&self.filter)
+    }
+
+    /// Decide when levels are coloured, by default only when writing to a terminal, see [`ColorMode`]
+    pub fn with_color(mut self, mode: ColorMode) -> Self {
+        self.color = mode.resolve_for(&self.writer);
+        self
+    }
+
+    /// Replace the styles used when colors are enabled, see [`Theme`]
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// Install this logger, replacing the active one if any. Panics if a logger from another crate is already installed
+    pub fn install(self) -> LoggerHandle {
+        match self.install_with(true) {
+            Ok(handle) => handle,
+            Err(err) => panic!("{err}"),
+        }
+    }
+
+    /// Install this logger unless one is already active
+    pub fn try_install(self) -> Result<LoggerHandle, InitError> {
+        self.install_with(false)
+    }
 
-    fn styled_level(level: Level) -> &'static str {
-        if cfg!(feature = "log_level_color") {
-            static LOG_LEVEL_NAMES: [&str; 6] = [
-                "\x1b[37mOFF\x1b[0m",     // White
-                "\x1b[91;1mERROR\x1b[0m", // Red
-                "\x1b[33mWARN\x1b[0m",    // Yellow
-                "\x1b[32mINFO\x1b[0m",    // Green
-                "\x1b[34mDEBUG\x1b[0m",   // Blue
-                "\x1b[36mTRACE\x1b[0m",   // Cyan
-            ];
-            LOG_LEVEL_NAMES[level as usize]
+    fn install_with(self, replace: bool) -> Result<LoggerHandle, InitError> {
+        let handle = self.handle();
+        let level = self.filter.max_level();
+        self.filter.set_installed();
+        if replace {
+            GlobalLogger::replace(self, level)?;
         } else {
-            level.as_str()
+            GlobalLogger::try_set(self, level)?;
         }
+        Ok(handle)
     }
 }
 
-impl<A, O> Log for BaseLogger<A, O>
+impl<A, O, F> Log for BaseLogger<A, O, F>
 where
     A: LogAppender,
     O: LogWriter,
+    F: LogFormatter,
 {
     fn enabled(&self, metadata: &Metadata) -> bool {
-        metadata.level() <= self.level
+        self.filter.enabled(metadata)
     }
 
     fn log(&self, record: &Record) {
-        let module = record.module_path_static().unwrap_or("unknown");
-        let mut stream = BufWriter::new(self.writer.get());
-
-        let _ = write!(stream, "[{} {} {}] ", Self::now(), Self::styled_level(record.level()), module);
-        // [time level module] - message
-        // [time level module] [extra] - message
-        if A::append(&mut stream) {
-            let _ = write!(stream, " ");
+        if !self.enabled(record.metadata()) {
+            return;
         }
-        let _ = writeln!(stream, "- {}", record.args());
-        let _ = stream.flush();
+
+        let mut extra = Vec::new();
+        let extra = if A::append(&mut extra) { Some(String::from_utf8_lossy(&extra)) } else { None };
+        let timestamp = self.clock.now();
+        let time = self.timestamp.render(timestamp);
+        let ctx = FormatContext {
+            timestamp,
+            time: time.as_deref(),
+            utc_offset: self.timestamp.zone().offset_at(timestamp.as_secs()),
+            extra: extra.as_deref(),
+            theme: self.color.then_some(&self.theme),
+        };
+
+        let _ = self.writer.write_record(record, &ctx, &self.formatter);
     }
 
-    fn flush(&self) {}
+    fn flush(&self) {
+        let _ = self.writer.sync();
+    }
 }
@@ -1,4 +1,23 @@
 pub mod appender;
+pub mod async_writer;
+pub mod builder;
+pub mod clock;
+#[cfg(feature = "config")]
+pub mod config;
+mod env;
+pub mod error;
+pub mod filter;
+pub mod formatter;
+pub mod global;
+pub mod handle;
+pub mod json;
+pub mod logfmt;
 #[allow(clippy::module_inception)]
 pub mod logger;
+pub mod pattern;
+pub mod retention;
+pub mod tee;
+pub mod theme;
+pub mod timestamp;
+pub(crate) mod tz;
 pub mod writer;
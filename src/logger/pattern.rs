@@ -0,0 +1,297 @@
+use std::{fmt::Display, io, io::Write, str::FromStr, time::Duration};
+
+use log::{Record, kv::Key};
+use utc_dt::{
+    UTCDatetime,
+    time::{UTCTimestamp, UTCTransformations},
+};
+
+use super::{error::*, formatter::*};
+
+/// PatternFormatter lays out lines from a pattern string such as
+/// `{d(%H:%M:%S%.3f)} {l:>5} {M}:{L} {X(trace_id)} - {m}{n}`. The pattern is parsed once when the formatter
+/// is created. Supported fields:
+///
+/// - `{d}` the logger's timestamp, `{d(format)}` the time in the timestamp's zone with
+///   `%Y %m %d %H %M %S %.3f %F %T %z %s %%`, where `%z` is the offset such as `+0800`
+/// - `{l}` level, `{t}` target, `{M}` module, `{f}` file, `{L}` line
+/// - `{m}` message, `{n}` newline, `{a}` LogAppender output
+/// - `{X(key)}` value of the record key-value `key`, `{K}` all key-values as ` key=value` pairs
+/// - `{T}` thread name, `{P}` process id
+///
+/// Any field takes a width, `{l:5}` and `{l:<5}` pad on the right, `{l:>5}` pads on the left.
+/// `{{` and `}}` are literal braces
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PatternFormatter {
+    pieces: Vec<Piece>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Piece {
+    Literal(String),
+    Field { field: Field, align: Option<Align> },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Field {
+    Date(Option<Vec<DateItem>>),
+    Level,
+    Target,
+    Module,
+    File,
+    Line,
+    Message,
+    Newline,
+    Extra,
+    Key(String),
+    KeyValues,
+    Thread,
+    Pid,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Align {
+    right: bool,
+    width: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum DateItem {
+    Literal(String),
+    Year,
+    Month,
+    Day,
+    Hour,
+    Minute,
+    Second,
+    Fraction(usize),
+    UnixSeconds,
+    Offset,
+}
+
+impl PatternFormatter {
+    pub fn new(pattern: &str) -> Result<Self, PatternError> {
+        let mut pieces = Vec::new();
+        let mut literal = String::new();
+        let mut chars = pattern.char_indices().peekable();
+
+        while let Some((position, c)) = chars.next() {
+            match c {
+                '{' if chars.next_if(|&(_, c)| c == '{').is_some() => literal.push('{'),
+                '}' if chars.next_if(|&(_, c)| c == '}').is_some() => literal.push('}'),
+                '}' => return Err(PatternError::new(position, "unmatched `}`, use `}}` for a literal brace")),
+                '{' => {
+                    let end = pattern[position..]
+                        .find('}')
+                        .map(|end| position + end)
+                        .ok_or_else(|| PatternError::new(position, "unclosed `{`"))?;
+                    while chars.next_if(|&(i, _)| i <= end).is_some() {}
+
+                    if !literal.is_empty() {
+                        pieces.push(Piece::Literal(std::mem::take(&mut literal)));
+                    }
+                    pieces.push(Self::parse_field(&pattern[position + 1..end], position)?);
+                }
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            pieces.push(Piece::Literal(literal));
+        }
+        Ok(Self { pieces })
+    }
+
+    /// Parses the inside of `{name(argument):align}`
+    fn parse_field(spec: &str, position: usize) -> Result<Piece, PatternError> {
+        let (spec, align) = match spec.rsplit_once(':').filter(|(_, align)| !align.contains(')')) {
+            Some((spec, align)) => (spec, Some(Self::parse_align(align, position)?)),
+            None => (spec, None),
+        };
+        let (name, argument) = match spec.split_once('(') {
+            Some((name, argument)) => {
+                let argument = argument
+                    .strip_suffix(')')
+                    .ok_or_else(|| PatternError::new(position, format!("missing `)` in `{{{spec}}}`")))?;
+                (name, Some(argument))
+            }
+            None => (spec, None),
+        };
+
+        let field = match (name, argument) {
+            ("d", None) => Field::Date(None),
+            ("d", Some(format)) => Field::Date(Some(Self::parse_date(format, position)?)),
+            ("l", None) => Field::Level,
+            ("t", None) => Field::Target,
+            ("M", None) => Field::Module,
+            ("f", None) => Field::File,
+            ("L", None) => Field::Line,
+            ("m", None) => Field::Message,
+            ("n", None) => Field::Newline,
+            ("a", None) => Field::Extra,
+            ("X", Some(key)) if !key.is_empty() => Field::Key(key.to_string()),
+            ("K", None) => Field::KeyValues,
+            ("T", None) => Field::Thread,
+            ("P", None) => Field::Pid,
+            _ => return Err(PatternError::new(position, format!("unknown field `{{{spec}}}`"))),
+        };
+        Ok(Piece::Field { field, align })
+    }
+
+    fn parse_align(align: &str, position: usize) -> Result<Align, PatternError> {
+        let (right, width) = match align.strip_prefix('>') {
+            Some(width) => (true, width),
+            None => (false, align.strip_prefix('<').unwrap_or(align)),
+        };
+        let width = width.parse().map_err(|_| PatternError::new(position, format!("invalid width `{align}`")))?;
+        Ok(Align { right, width })
+    }
+
+    fn parse_date(format: &str, position: usize) -> Result<Vec<DateItem>, PatternError> {
+        let mut items = Vec::new();
+        let mut literal = String::new();
+        let mut chars = format.chars();
+
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                literal.push(c);
+                continue;
+            }
+            let item = match chars.next() {
+                Some('%') => {
+                    literal.push('%');
+                    continue;
+                }
+                Some('Y') => vec![DateItem::Year],
+                Some('m') => vec![DateItem::Month],
+                Some('d') => vec![DateItem::Day],
+                Some('H') => vec![DateItem::Hour],
+                Some('M') => vec![DateItem::Minute],
+                Some('S') => vec![DateItem::Second],
+                Some('s') => vec![DateItem::UnixSeconds],
+                Some('z') => vec![DateItem::Offset],
+                Some('F') => vec![
+                    DateItem::Year,
+                    DateItem::Literal("-".into()),
+                    DateItem::Month,
+                    DateItem::Literal("-".into()),
+                    DateItem::Day,
+                ],
+                Some('T') => vec![
+                    DateItem::Hour,
+                    DateItem::Literal(":".into()),
+                    DateItem::Minute,
+                    DateItem::Literal(":".into()),
+                    DateItem::Second,
+                ],
+                // %.3f %.6f %.9f
+                Some('.') => match (chars.next().and_then(|c| c.to_digit(10)), chars.next()) {
+                    (Some(digits @ 1..=9), Some('f')) => {
+                        vec![DateItem::Literal(".".into()), DateItem::Fraction(digits as usize)]
+                    }
+                    _ => {
+                        return Err(PatternError::new(position, format!("invalid fraction in date format `{format}`")));
+                    }
+                },
+                Some(c) => return Err(PatternError::new(position, format!("unknown date specifier `%{c}`"))),
+                None => return Err(PatternError::new(position, "date format ends with `%`")),
+            };
+            if !literal.is_empty() {
+                items.push(DateItem::Literal(std::mem::take(&mut literal)));
+            }
+            items.extend(item);
+        }
+        if !literal.is_empty() {
+            items.push(DateItem::Literal(literal));
+        }
+        Ok(items)
+    }
+
+    fn write_date(out: &mut dyn Write, items: &[DateItem], timestamp: Duration, utc_offset: i32) -> io::Result<()> {
+        let local = Duration::new(timestamp.as_secs().saturating_add_signed(utc_offset as i64), 0);
+        let (date, tod) = UTCDatetime::from_timestamp(UTCTimestamp::from_duration(local)).to_components();
+        let (year, month, day) = date.to_components();
+        let (hour, minute, second) = tod.as_hhmmss();
+        for item in items {
+            match item {
+                DateItem::Literal(literal) => out.write_all(literal.as_bytes())?,
+                DateItem::Year => write!(out, "{year:04}")?,
+                DateItem::Month => write!(out, "{month:02}")?,
+                DateItem::Day => write!(out, "{day:02}")?,
+                DateItem::Hour => write!(out, "{hour:02}")?,
+                DateItem::Minute => write!(out, "{minute:02}")?,
+                DateItem::Second => write!(out, "{second:02}")?,
+                DateItem::Fraction(digits) => {
+                    let fraction = timestamp.subsec_nanos() / 10u32.pow(9 - *digits as u32);
+                    write!(out, "{fraction:0digits$}")?
+                }
+                DateItem::UnixSeconds => write!(out, "{}", timestamp.as_secs())?,
+                DateItem::Offset => {
+                    let sign = if utc_offset < 0 { '-' } else { '+' };
+                    let minutes = utc_offset.unsigned_abs() / 60;
+                    write!(out, "{sign}{:02}{:02}", minutes / 60, minutes % 60)?
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn write_field(out: &mut dyn Write, field: &Field, record: &Record, ctx: &FormatContext) -> io::Result<()> {
+        fn display(out: &mut dyn Write, value: impl Display) -> io::Result<()> {
+            write!(out, "{value}")
+        }
+
+        match field {
+            Field::Date(None) => display(out, ctx.time.unwrap_or_default()),
+            Field::Date(Some(items)) => Self::write_date(out, items, ctx.timestamp, ctx.utc_offset),
+            Field::Level => display(out, record.level()),
+            Field::Target => display(out, record.target()),
+            Field::Module => display(out, record.module_path().unwrap_or("unknown")),
+            Field::File => display(out, record.file().unwrap_or("unknown")),
+            Field::Line => match record.line() {
+                Some(line) => display(out, line),
+                None => Ok(()),
+            },
+            Field::Message => display(out, record.args()),
+            Field::Newline => writeln!(out),
+            Field::Extra => display(out, ctx.extra.unwrap_or_default()),
+            Field::Key(key) => match record.key_values().get(Key::from_str(key)) {
+                Some(value) => display(out, value),
+                None => Ok(()),
+            },
+            Field::KeyValues => write_key_values(out, record),
+            Field::Thread => display(out, std::thread::current().name().unwrap_or("unnamed")),
+            Field::Pid => display(out, std::process::id()),
+        }
+    }
+}
+
+impl FromStr for PatternFormatter {
+    type Err = PatternError;
+
+    fn from_str(pattern: &str) -> Result<Self, Self::Err> {
+        Self::new(pattern)
+    }
+}
+
+impl LogFormatter for PatternFormatter {
+    fn format(&self, out: &mut dyn Write, record: &Record, ctx: &FormatContext) -> io::Result<()> {
+        for piece in &self.pieces {
+            match piece {
+                Piece::Literal(literal) => out.write_all(literal.as_bytes())?,
+                Piece::Field { field, align: None } => Self::write_field(out, field, record, ctx)?,
+                Piece::Field { field, align: Some(align) } => {
+                    let mut value = Vec::new();
+                    Self::write_field(&mut value, field, record, ctx)?;
+                    let value = String::from_utf8_lossy(&value);
+                    let width = align.width;
+                    if align.right {
+                        write!(out, "{value:>width$}")?;
+                    } else {
+                        write!(out, "{value:<width$}")?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
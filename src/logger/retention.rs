@@ -0,0 +1,158 @@
+use std::{
+    ffi::OsStr,
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+    thread::{self, JoinHandle},
+    time::{Duration, SystemTime},
+};
+
+use log::Level;
+
+/// RetentionPolicy decides what happens to the files a rotating writer has rotated out.
+/// It runs on a background thread, so it may compress or delete files without blocking logging
+pub trait RetentionPolicy: Send + Sync + 'static {
+    /// `closed` is the file that was just rotated out, `archived` lists every rotated file of the writer
+    /// (including `closed`), newest first
+    fn apply(&self, closed: &Path, archived: &[PathBuf]);
+}
+
+/// Retention optionally gzip-compresses rotated files and deletes the ones that are older than `max_age`
+/// or that no longer fit in `max_total_size` bytes, newest files are kept first
+#[derive(Clone, Debug, Default)]
+pub struct Retention {
+    #[cfg(feature = "gzip")]
+    compress: bool,
+    max_age: Option<Duration>,
+    max_total_size: Option<u64>,
+}
+
+impl Retention {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Compress rotated files to `<name>.gz`
+    #[cfg(feature = "gzip")]
+    pub fn compress(mut self, compress: bool) -> Self {
+        self.compress = compress;
+        self
+    }
+
+    /// Delete rotated files last modified more than `max_age` ago
+    pub fn max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    /// Delete the oldest rotated files once all of them together exceed `max_total_size` bytes
+    pub fn max_total_size(mut self, max_total_size: u64) -> Self {
+        self.max_total_size = Some(max_total_size);
+        self
+    }
+}
+
+impl Retention {
+    #[cfg(feature = "gzip")]
+    fn compress_closed(&self, closed: &Path, archived: &[PathBuf]) -> Vec<PathBuf> {
+        if !self.compress {
+            return archived.to_vec();
+        }
+        match gzip(closed) {
+            Ok(compressed) => {
+                archived.iter().map(|path| if path == closed { compressed.clone() } else { path.clone() }).collect()
+            }
+            Err(err) => {
+                crate::log_print!(Level::Warn, "failed to compress {}: {}", closed.display(), err);
+                archived.to_vec()
+            }
+        }
+    }
+}
+
+impl RetentionPolicy for Retention {
+    fn apply(&self, closed: &Path, archived: &[PathBuf]) {
+        #[cfg(feature = "gzip")]
+        let archived = &self.compress_closed(closed, archived);
+        #[cfg(not(feature = "gzip"))]
+        let _ = closed;
+
+        let now = SystemTime::now();
+        let mut total_size = 0;
+        for path in archived {
+            let Ok(metadata) = fs::metadata(path) else {
+                continue;
+            };
+            let age = metadata.modified().ok().and_then(|modified| now.duration_since(modified).ok());
+            let expired = matches!((self.max_age, age), (Some(max_age), Some(age)) if age > max_age);
+            let over_budget = self.max_total_size.is_some_and(|max| total_size + metadata.len() > max);
+            if expired || over_budget {
+                if let Err(err) = fs::remove_file(path) {
+                    crate::log_print!(Level::Warn, "failed to remove {}: {}", path.display(), err);
+                }
+            } else {
+                total_size += metadata.len();
+            }
+        }
+    }
+}
+
+/// `app.log.1` becomes `app.log.1.gz`
+pub(crate) fn with_suffix(path: &Path, suffix: impl AsRef<OsStr>) -> PathBuf {
+    let mut path = path.to_path_buf().into_os_string();
+    path.push(suffix);
+    path.into()
+}
+
+#[cfg(feature = "gzip")]
+fn gzip(path: &Path) -> std::io::Result<PathBuf> {
+    use std::{fs::File, io};
+
+    use flate2::{Compression, write::GzEncoder};
+
+    let compressed = with_suffix(path, ".gz");
+    let mut encoder = GzEncoder::new(File::create(&compressed)?, Compression::default());
+    io::copy(&mut File::open(path)?, &mut encoder)?;
+    encoder.finish()?.sync_all()?;
+    fs::remove_file(path)?;
+    Ok(compressed)
+}
+
+/// Archiver runs a writer's retention policy on a background thread. Only one run is in flight at a time,
+/// the next rotation waits for it so files are never renamed while the policy is still working on them
+pub(crate) struct Archiver {
+    policy: Arc<dyn RetentionPolicy>,
+    pending: Option<JoinHandle<()>>,
+}
+
+impl Archiver {
+    pub(crate) fn new(policy: impl RetentionPolicy) -> Self {
+        Self { policy: Arc::new(policy), pending: None }
+    }
+
+    pub(crate) fn wait(&mut self) {
+        if let Some(pending) = self.pending.take() {
+            let _ = pending.join();
+        }
+    }
+
+    pub(crate) fn archive(&mut self, closed: PathBuf, archived: Vec<PathBuf>) {
+        self.wait();
+        let policy = self.policy.clone();
+        // this runs under the file lock, where a panic would poison it, so a failed spawn only skips this run
+        let spawned =
+            thread::Builder::new().name("rs_logger-retention".into()).spawn(move || policy.apply(&closed, &archived));
+        match spawned {
+            Ok(pending) => self.pending = Some(pending),
+            Err(err) => {
+                crate::log_print!(Level::Warn, "failed to start the retention policy: {err}");
+            }
+        }
+    }
+}
+
+impl Drop for Archiver {
+    fn drop(&mut self) {
+        self.wait();
+    }
+}
@@ -0,0 +1,129 @@
+use std::io;
+
+use log::{Level, LevelFilter, Record};
+
+use super::{formatter::*, theme::*, writer::*};
+
+/// Tee sends each record to several sinks, each with its own level, formatter and colors:
+///
+/// ```rust
+/// use log::LevelFilter;
+/// use rs_logger::{BaseLogger, FileOptions, JsonFormatter, LogFileWriter, NopAppender, Sink, Stderr, Tee};
+///
+/// # let path = std::env::temp_dir().join("rs_logger_tee_doc.log");
+/// let file = LogFileWriter::open(&path, &FileOptions::default()).unwrap();
+/// let tee = Tee::new()
+///     .with_sink(Sink::new(Stderr).with_level(LevelFilter::Error))
+///     .with_sink(Sink::new(file).with_level(LevelFilter::Debug).with_formatter(JsonFormatter));
+/// BaseLogger::<NopAppender, _>::new(LevelFilter::Debug, tee).install();
+/// # std::fs::remove_file(&path).unwrap();
+/// ```
+///
+/// The logger's level is checked first, so it has to be at least as verbose as the most verbose sink
+#[derive(Default)]
+pub struct Tee {
+    sinks: Vec<Sink>,
+}
+
+impl Tee {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_sink(mut self, sink: Sink) -> Self {
+        self.sinks.push(sink);
+        self
+    }
+}
+
+impl LogWriter for Tee {
+    type Stream = Fanout<Box<dyn io::Write>>;
+
+    /// Raw bytes go to every sink, regardless of its level
+    fn get(&self) -> Self::Stream {
+        Fanout(self.sinks.iter().map(|sink| sink.writer.get()).collect())
+    }
+
+    fn is_file(&self) -> bool {
+        self.sinks.iter().any(|sink| sink.writer.is_file())
+    }
+
+    fn write_record(&self, record: &Record, ctx: &FormatContext, formatter: &dyn LogFormatter) -> io::Result<()> {
+        let mut result = Ok(());
+        for sink in self.sinks.iter().filter(|sink| record.level() <= sink.level) {
+            let ctx = FormatContext { theme: sink.color.then_some(&sink.theme), ..*ctx };
+            let formatter = sink.formatter.as_deref().unwrap_or(formatter);
+            // one failing sink doesn't stop the others, the first error is reported
+            if let Err(err) = sink.writer.write_record(record, &ctx, formatter) {
+                result = result.and(Err(err));
+            }
+        }
+        result
+    }
+
+    /// The line is already formatted, so it goes to every sink that takes its level as is, without the sink's
+    /// formatter or colors
+    fn write_line(&self, level: Level, line: &[u8]) -> io::Result<()> {
+        let mut result = Ok(());
+        for sink in self.sinks.iter().filter(|sink| level <= sink.level) {
+            if let Err(err) = sink.writer.write_line(level, line) {
+                result = result.and(Err(err));
+            }
+        }
+        result
+    }
+
+    fn sync(&self) -> io::Result<()> {
+        let mut result = Ok(());
+        for sink in &self.sinks {
+            if let Err(err) = sink.writer.sync() {
+                result = result.and(Err(err));
+            }
+        }
+        result
+    }
+}
+
+/// Sink is one destination of a [`Tee`]. By default it takes every record the logger lets through, uses the
+/// logger's formatter and colors only when writing to a terminal
+pub struct Sink {
+    writer: BoxWriter,
+    level: LevelFilter,
+    formatter: Option<Box<dyn LogFormatter>>,
+    color: bool,
+    theme: Theme,
+}
+
+impl Sink {
+    pub fn new<W>(writer: W) -> Self
+    where
+        W: LogWriter,
+        W::Stream: 'static,
+    {
+        let writer = BoxWriter::new(writer);
+        let color = ColorMode::default().resolve_for(&writer);
+        Self { writer, level: LevelFilter::Trace, formatter: None, color, theme: Theme::default() }
+    }
+
+    /// Only write records at or above this level
+    pub fn with_level(mut self, level: LevelFilter) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// Format this sink's lines with `formatter` instead of the logger's
+    pub fn with_formatter(mut self, formatter: impl LogFormatter) -> Self {
+        self.formatter = Some(Box::new(formatter));
+        self
+    }
+
+    pub fn with_color(mut self, mode: ColorMode) -> Self {
+        self.color = mode.resolve_for(&self.writer);
+        self
+    }
+
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+}
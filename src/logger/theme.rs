@@ -0,0 +1,245 @@
+use std::fmt;
+
+use log::Level;
+
+/// Color of a piece of text
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Color {
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+    BrightBlack,
+    BrightRed,
+    BrightGreen,
+    BrightYellow,
+    BrightBlue,
+    BrightMagenta,
+    BrightCyan,
+    BrightWhite,
+    /// One of the 256 colors of the xterm palette
+    Ansi256(u8),
+    /// 24-bit truecolor
+    Rgb(u8, u8, u8),
+}
+
+impl Color {
+    /// SGR parameters selecting this color, `base` is 30 for the foreground and 40 for the background
+    fn write_sgr(&self, f: &mut fmt::Formatter, base: u8) -> fmt::Result {
+        let basic = |offset: u8| base + offset;
+        match *self {
+            Color::Black => write!(f, "{}", basic(0)),
+            Color::Red => write!(f, "{}", basic(1)),
+            Color::Green => write!(f, "{}", basic(2)),
+            Color::Yellow => write!(f, "{}", basic(3)),
+            Color::Blue => write!(f, "{}", basic(4)),
+            Color::Magenta => write!(f, "{}", basic(5)),
+            Color::Cyan => write!(f, "{}", basic(6)),
+            Color::White => write!(f, "{}", basic(7)),
+            Color::BrightBlack => write!(f, "{}", basic(60)),
+            Color::BrightRed => write!(f, "{}", basic(61)),
+            Color::BrightGreen => write!(f, "{}", basic(62)),
+            Color::BrightYellow => write!(f, "{}", basic(63)),
+            Color::BrightBlue => write!(f, "{}", basic(64)),
+            Color::BrightMagenta => write!(f, "{}", basic(65)),
+            Color::BrightCyan => write!(f, "{}", basic(66)),
+            Color::BrightWhite => write!(f, "{}", basic(67)),
+            Color::Ansi256(index) => write!(f, "{};5;{index}", base + 8),
+            Color::Rgb(r, g, b) => write!(f, "{};2;{r};{g};{b}", base + 8),
+        }
+    }
+}
+
+/// Style is a combination of colors and text attributes, `Style::new().fg(Color::Red).bold()`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Style {
+    fg: Option<Color>,
+    bg: Option<Color>,
+    bold: bool,
+    dim: bool,
+    italic: bool,
+    underline: bool,
+}
+
+impl Style {
+    /// Plain text
+    pub const fn new() -> Self {
+        Self { fg: None, bg: None, bold: false, dim: false, italic: false, underline: false }
+    }
+
+    pub const fn fg(mut self, color: Color) -> Self {
+        self.fg = Some(color);
+        self
+    }
+
+    pub const fn bg(mut self, color: Color) -> Self {
+        self.bg = Some(color);
+        self
+    }
+
+    pub const fn bold(mut self) -> Self {
+        self.bold = true;
+        self
+    }
+
+    pub const fn dim(mut self) -> Self {
+        self.dim = true;
+        self
+    }
+
+    pub const fn italic(mut self) -> Self {
+        self.italic = true;
+        self
+    }
+
+    pub const fn underline(mut self) -> Self {
+        self.underline = true;
+        self
+    }
+
+    pub fn is_plain(&self) -> bool {
+        *self == Self::new()
+    }
+
+    /// `value` wrapped in the escape sequences of this style
+    pub fn paint<T: fmt::Display>(&self, value: T) -> Painted<'_, T> {
+        Painted { style: self, value }
+    }
+}
+
+/// A value displayed with a Style, see [`Style::paint`]
+pub struct Painted<'a, T> {
+    style: &'a Style,
+    value: T,
+}
+
+impl<T: fmt::Display> fmt::Display for Painted<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let style = self.style;
+        if style.is_plain() {
+            return self.value.fmt(f);
+        }
+
+        write!(f, "\x1b[")?;
+        let mut separator = "";
+        let mut next = |f: &mut fmt::Formatter| {
+            let result = f.write_str(separator);
+            separator = ";";
+            result
+        };
+        if let Some(fg) = style.fg {
+            next(f)?;
+            fg.write_sgr(f, 30)?;
+        }
+        if let Some(bg) = style.bg {
+            next(f)?;
+            bg.write_sgr(f, 40)?;
+        }
+        for (enabled, code) in [(style.bold, "1"), (style.dim, "2"), (style.italic, "3"), (style.underline, "4")] {
+            if enabled {
+                next(f)?;
+                f.write_str(code)?;
+            }
+        }
+        write!(f, "m{}\x1b[0m", self.value)
+    }
+}
+
+/// Theme holds the styles of the parts of a log line, used when colors are enabled. Everything but the level is
+/// plain in the default theme, the message style applies to the whole message of a level, which can make errors
+/// and warnings stand out
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Theme {
+    levels: [Style; 5],
+    messages: [Style; 5],
+    timestamp: Style,
+    module: Style,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            levels: [
+                Style::new().fg(Color::BrightRed).bold(),
+                Style::new().fg(Color::Yellow),
+                Style::new().fg(Color::Green),
+                Style::new().fg(Color::Blue),
+                Style::new().fg(Color::Cyan),
+            ],
+            messages: [Style::new(); 5],
+            timestamp: Style::new(),
+            module: Style::new(),
+        }
+    }
+}
+
+impl Theme {
+    /// Bold, bright colors on every level with errors and warnings colored as a whole, for low contrast
+    /// terminals and projectors
+    pub fn high_contrast() -> Self {
+        Self {
+            levels: [
+                Style::new().fg(Color::BrightWhite).bg(Color::Red).bold(),
+                Style::new().fg(Color::Black).bg(Color::BrightYellow).bold(),
+                Style::new().fg(Color::BrightGreen).bold(),
+                Style::new().fg(Color::BrightCyan).bold(),
+                Style::new().fg(Color::BrightWhite).bold(),
+            ],
+            messages: [
+                Style::new().fg(Color::BrightRed).bold(),
+                Style::new().fg(Color::BrightYellow).bold(),
+                Style::new(),
+                Style::new(),
+                Style::new(),
+            ],
+            timestamp: Style::new().fg(Color::BrightWhite),
+            module: Style::new().fg(Color::BrightMagenta).bold(),
+        }
+    }
+
+    /// Default level colors with the timestamp and module dimmed, so the message is what catches the eye
+    pub fn subtle() -> Self {
+        Self { timestamp: Style::new().dim(), module: Style::new().dim(), ..Self::default() }
+    }
+
+    pub fn with_level(mut self, level: Level, style: Style) -> Self {
+        self.levels[level as usize - 1] = style;
+        self
+    }
+
+    /// Style of the message, and its key-values, of records at `level`
+    pub fn with_message(mut self, level: Level, style: Style) -> Self {
+        self.messages[level as usize - 1] = style;
+        self
+    }
+
+    pub fn with_timestamp(mut self, style: Style) -> Self {
+        self.timestamp = style;
+        self
+    }
+
+    pub fn with_module(mut self, style: Style) -> Self {
+        self.module = style;
+        self
+    }
+
+    pub fn level(&self, level: Level) -> &Style {
+        &self.levels[level as usize - 1]
+    }
+
+    pub fn message(&self, level: Level) -> &Style {
+        &self.messages[level as usize - 1]
+    }
+
+    pub fn timestamp(&self) -> &Style {
+        &self.timestamp
+    }
+
+    pub fn module(&self) -> &Style {
+        &self.module
+    }
+}
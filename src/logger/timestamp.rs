@@ -0,0 +1,187 @@
+use std::{sync::Arc, time::Duration};
+
+use utc_dt::{
+    UTCDatetime,
+    time::{UTCTimestamp, UTCTransformations},
+};
+
+use super::tz::ZoneInfo;
+
+/// TimeZone in which timestamps are rendered
+#[derive(Clone, Debug, PartialEq)]
+pub struct TimeZone(Zone);
+
+#[derive(Clone, Debug, PartialEq)]
+enum Zone {
+    Utc,
+    Fixed(i32),
+    Local(Arc<ZoneInfo>),
+}
+
+impl TimeZone {
+    pub fn utc() -> Self {
+        TimeZone(Zone::Utc)
+    }
+
+    /// A fixed offset in seconds east of UTC, `8 * 3600` for UTC+08:00
+    pub fn fixed(offset: i32) -> Self {
+        TimeZone(Zone::Fixed(offset))
+    }
+
+    /// The system time zone, read from `TZ` or `/etc/localtime` without touching the network.
+    /// Falls back to UTC if neither can be read
+    pub fn local() -> Self {
+        match ZoneInfo::local() {
+            Some(zone) => TimeZone(Zone::Local(Arc::new(zone))),
+            None => Self::utc(),
+        }
+    }
+
+    /// A TZ database name such as `Asia/Shanghai` or a POSIX TZ string such as `CST-8`
+    pub fn named(name: &str) -> Option<Self> {
+        ZoneInfo::from_tz_var(name).map(|zone| TimeZone(Zone::Local(Arc::new(zone))))
+    }
+
+    /// UTC offset in seconds at `secs` seconds since the epoch
+    pub fn offset_at(&self, secs: u64) -> i32 {
+        match &self.0 {
+            Zone::Utc => 0,
+            Zone::Fixed(offset) => *offset,
+            Zone::Local(zone) => zone.offset_at(secs as i64),
+        }
+    }
+}
+
+impl Default for TimeZone {
+    fn default() -> Self {
+        Self::utc()
+    }
+}
+
+/// TimestampFormat is the layout of a rendered timestamp
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimestampFormat {
+    /// `2026-10-17T03:04:05.123Z` in UTC, the wall-clock time without an offset in other zones
+    Iso,
+    /// `2026-10-17T11:04:05.123+08:00`, always with the offset
+    Rfc3339,
+    /// `1792206245.123`
+    UnixSeconds,
+    /// `1792206245123`
+    UnixMillis,
+}
+
+/// Timestamp is the policy for the time shown in front of each record. The default matches the original
+/// layout, UTC in ISO 8601 with millisecond precision
+#[derive(Clone, Debug, PartialEq)]
+pub struct Timestamp {
+    format: Option<TimestampFormat>,
+    zone: TimeZone,
+    precision: usize,
+}
+
+impl Default for Timestamp {
+    fn default() -> Self {
+        Self { format: Some(TimestampFormat::Iso), zone: TimeZone::utc(), precision: 3 }
+    }
+}
+
+impl Timestamp {
+    pub fn new(format: TimestampFormat) -> Self {
+        Self { format: Some(format), ..Self::default() }
+    }
+
+    /// No timestamp at all, for environments that stamp lines themselves
+    pub fn none() -> Self {
+        Self { format: None, ..Self::default() }
+    }
+
+    pub fn with_zone(mut self, zone: TimeZone) -> Self {
+        self.zone = zone;
+        self
+    }
+
+    pub fn zone(&self) -> &TimeZone {
+        &self.zone
+    }
+
+    /// Number of fractional second digits, 0 to 9
+    pub fn with_precision(mut self, precision: usize) -> Self {
+        self.precision = precision.min(9);
+        self
+    }
+
+    /// Drop the timestamp when stderr is connected to the systemd journal, which stamps every line itself.
+    /// systemd sets `JOURNAL_STREAM` to the `device:inode` of the stream it hands to a service. Child processes
+    /// inherit the variable even when their stderr goes elsewhere, so it has to match stderr
+    pub fn unless_journald(self) -> Self {
+        if stderr_is_journal() { Self::none() } else { self }
+    }
+
+    /// Render `timestamp`, the time since the Unix epoch. `None` when timestamps are disabled
+    pub fn render(&self, timestamp: Duration) -> Option<String> {
+        let format = self.format?;
+        let secs = timestamp.as_secs();
+        let nanos = timestamp.subsec_nanos();
+        Some(match format {
+            TimestampFormat::UnixSeconds if self.precision == 0 => secs.to_string(),
+            TimestampFormat::UnixSeconds => {
+                let fraction = nanos / 10u32.pow(9 - self.precision as u32);
+                format!("{secs}.{fraction:0width$}", width = self.precision)
+            }
+            TimestampFormat::UnixMillis => timestamp.as_millis().to_string(),
+            TimestampFormat::Iso | TimestampFormat::Rfc3339 => {
+                let offset = self.zone.offset_at(secs);
+                let local = secs.saturating_add_signed(offset as i64);
+                let datetime = UTCDatetime::from_timestamp(UTCTimestamp::from_duration(Duration::new(local, nanos)));
+                let mut time = datetime.as_iso_datetime(self.precision);
+                match (format, self.zone.0 == Zone::Utc) {
+                    (TimestampFormat::Iso, true) => {}
+                    (TimestampFormat::Iso, false) => {
+                        time.pop();
+                    }
+                    _ => {
+                        time.pop();
+                        let sign = if offset < 0 { '-' } else { '+' };
+                        let offset = offset.unsigned_abs();
+                        time += &format!("{sign}{:02}:{:02}", offset / 3600, offset % 3600 / 60);
+                    }
+                }
+                time
+            }
+        })
+    }
+}
+
+#[cfg(unix)]
+fn stderr_is_journal() -> bool {
+    use std::{fs::File, io, os::fd::AsFd};
+
+    let Some(stream) = std::env::var_os("JOURNAL_STREAM") else {
+        return false;
+    };
+    // a duplicate of stderr, so closing it leaves stderr open
+    match io::stderr().as_fd().try_clone_to_owned() {
+        Ok(fd) => is_journal_stream(&stream, &File::from(fd)),
+        Err(_) => false,
+    }
+}
+
+#[cfg(not(unix))]
+fn stderr_is_journal() -> bool {
+    false
+}
+
+/// Whether `file` is the stream described by a `JOURNAL_STREAM` value
+#[cfg(unix)]
+pub(crate) fn is_journal_stream(value: &std::ffi::OsStr, file: &std::fs::File) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    let Some((device, inode)) = value.to_str().and_then(|value| value.split_once(':')) else {
+        return false;
+    };
+    match (device.parse::<u64>(), inode.parse::<u64>(), file.metadata()) {
+        (Ok(device), Ok(inode), Ok(meta)) => meta.dev() == device && meta.ino() == inode,
+        _ => false,
+    }
+}
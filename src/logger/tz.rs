@@ -0,0 +1,370 @@
+use std::{env, fs, path::Path};
+
+/// Rules of a time zone, read from TZif files such as `/etc/localtime` or from a POSIX `TZ` string.
+/// This is just enough of the TZ database to know the UTC offset at a given instant
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct ZoneInfo {
+    /// Transition times in seconds since the epoch, each with the UTC offset that starts at it
+    transitions: Vec<(i64, i32)>,
+    /// Offset before the first transition
+    initial_offset: i32,
+    /// Rule for instants after the last transition
+    rule: Option<PosixRule>,
+}
+
+impl ZoneInfo {
+    /// Zone of this machine: `TZ` when set, `/etc/localtime` otherwise. `None` if neither can be read,
+    /// an empty `TZ` means UTC
+    pub(crate) fn local() -> Option<Self> {
+        match env::var("TZ") {
+            Ok(tz) if tz.is_empty() => Some(Self::fixed(0)),
+            Ok(tz) => Self::from_tz_var(tz.strip_prefix(':').unwrap_or(&tz)),
+            Err(_) => Self::from_tzif(&fs::read("/etc/localtime").ok()?),
+        }
+    }
+
+    /// `tz` is a TZ database name such as `Asia/Shanghai`, a path to a TZif file or a POSIX TZ string
+    pub(crate) fn from_tz_var(tz: &str) -> Option<Self> {
+        if tz.starts_with('/') {
+            return Self::from_tzif(&fs::read(tz).ok()?);
+        }
+        if !tz.contains("..") {
+            for dir in ["/usr/share/zoneinfo", "/usr/lib/zoneinfo", "/usr/share/lib/zoneinfo"] {
+                if let Ok(data) = fs::read(Path::new(dir).join(tz)) {
+                    return Self::from_tzif(&data);
+                }
+            }
+        }
+        let rule = PosixRule::parse(tz)?;
+        Some(Self { transitions: Vec::new(), initial_offset: rule.std_offset, rule: Some(rule) })
+    }
+
+    pub(crate) fn fixed(offset: i32) -> Self {
+        Self { transitions: Vec::new(), initial_offset: offset, rule: None }
+    }
+
+    /// Parses a TZif file, version 2 and later data is preferred when present
+    pub(crate) fn from_tzif(data: &[u8]) -> Option<Self> {
+        let mut reader = Reader { data, position: 0 };
+        let header = reader.header()?;
+        if header.version >= b'2' {
+            // skip the 32-bit data block, the 64-bit one follows with its own header
+            reader.skip(header.data_len(4)?)?;
+            let header = reader.header()?;
+            let mut zone = reader.body(&header, 8)?;
+            zone.rule = reader.footer().and_then(PosixRule::parse);
+            Some(zone)
+        } else {
+            reader.body(&header, 4)
+        }
+    }
+
+    /// UTC offset in seconds at `time` seconds since the epoch
+    pub(crate) fn offset_at(&self, time: i64) -> i32 {
+        let index = self.transitions.partition_point(|&(at, _)| at <= time);
+        match (&self.rule, index) {
+            (Some(rule), index) if index == self.transitions.len() => rule.offset_at(time),
+            (_, 0) => self.initial_offset,
+            (_, index) => self.transitions[index - 1].1,
+        }
+    }
+}
+
+struct Header {
+    version: u8,
+    isutcnt: usize,
+    isstdcnt: usize,
+    leapcnt: usize,
+    timecnt: usize,
+    typecnt: usize,
+    charcnt: usize,
+}
+
+impl Header {
+    /// Size of the data block after the header, `None` if the counts overflow
+    fn data_len(&self, time_size: usize) -> Option<usize> {
+        let sizes = [
+            self.timecnt.checked_mul(time_size + 1)?,
+            self.typecnt.checked_mul(6)?,
+            self.charcnt,
+            self.leapcnt.checked_mul(time_size + 4)?,
+            self.isstdcnt,
+            self.isutcnt,
+        ];
+        sizes.into_iter().try_fold(0usize, usize::checked_add)
+    }
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        let bytes = self.data.get(self.position..self.position.checked_add(len)?)?;
+        self.position += len;
+        Some(bytes)
+    }
+
+    fn skip(&mut self, len: usize) -> Option<()> {
+        self.take(len).map(|_| ())
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        Some(u32::from_be_bytes(self.take(4)?.try_into().ok()?))
+    }
+
+    fn i32(&mut self) -> Option<i32> {
+        Some(i32::from_be_bytes(self.take(4)?.try_into().ok()?))
+    }
+
+    fn time(&mut self, size: usize) -> Option<i64> {
+        match size {
+            8 => Some(i64::from_be_bytes(self.take(8)?.try_into().ok()?)),
+            _ => self.i32().map(i64::from),
+        }
+    }
+
+    fn header(&mut self) -> Option<Header> {
+        if self.take(4)? != b"TZif" {
+            return None;
+        }
+        let version = self.take(1)?[0];
+        self.skip(15)?;
+        let mut count = || self.u32().map(|count| count as usize);
+        Some(Header {
+            version,
+            isutcnt: count()?,
+            isstdcnt: count()?,
+            leapcnt: count()?,
+            timecnt: count()?,
+            typecnt: count()?,
+            charcnt: count()?,
+        })
+    }
+
+    fn body(&mut self, header: &Header, time_size: usize) -> Option<ZoneInfo> {
+        // the counts come from the file, check them against its size before allocating
+        if header.data_len(time_size)? > self.data.len() - self.position {
+            return None;
+        }
+        let times = (0..header.timecnt).map(|_| self.time(time_size)).collect::<Option<Vec<_>>>()?;
+        let indices = self.take(header.timecnt)?;
+        let mut types = Vec::with_capacity(header.typecnt);
+        for _ in 0..header.typecnt {
+            let offset = self.i32()?;
+            let is_dst = self.take(2)?[0] != 0;
+            types.push((offset, is_dst));
+        }
+        self.skip(header.charcnt + header.leapcnt * (time_size + 4) + header.isstdcnt + header.isutcnt)?;
+
+        let transitions = times
+            .into_iter()
+            .zip(indices)
+            .map(|(time, &index)| types.get(index as usize).map(|&(offset, _)| (time, offset)))
+            .collect::<Option<Vec<_>>>()?;
+        // before the first transition the first standard time type applies
+        let initial_offset = types.iter().find(|(_, is_dst)| !is_dst).or(types.first()).map(|&(offset, _)| offset)?;
+        Some(ZoneInfo { transitions, initial_offset, rule: None })
+    }
+
+    /// The POSIX TZ string between the newlines at the end of a version 2+ file
+    fn footer(&mut self) -> Option<&'a str> {
+        let rest = std::str::from_utf8(self.data.get(self.position..)?).ok()?;
+        let footer = rest.strip_prefix('\n')?.split('\n').next()?;
+        (!footer.is_empty()).then_some(footer)
+    }
+}
+
+/// A POSIX TZ string such as `CST-8` or `EST5EDT,M3.2.0,M11.1.0`
+#[derive(Clone, Debug, PartialEq)]
+struct PosixRule {
+    std_offset: i32,
+    dst: Option<Dst>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Dst {
+    offset: i32,
+    start: (DateRule, i32),
+    end: (DateRule, i32),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum DateRule {
+    /// `Jn`, day 1 to 365 ignoring February 29
+    Julian(u16),
+    /// `n`, day 0 to 365 counting February 29
+    Zero(u16),
+    /// `Mm.w.d`, day `d` (0 is Sunday) of week `w` (5 is the last) of month `m`
+    Month(u8, u8, u8),
+}
+
+impl PosixRule {
+    fn parse(tz: &str) -> Option<Self> {
+        let mut parser = PosixParser { rest: tz };
+        parser.name()?;
+        // POSIX offsets count hours west of Greenwich, so the sign is flipped
+        let std_offset = -parser.offset()?;
+        if parser.rest.is_empty() {
+            return Some(Self { std_offset, dst: None });
+        }
+
+        parser.name()?;
+        let dst_offset = match parser.rest.chars().next() {
+            Some(',') | None => std_offset + 3600,
+            _ => -parser.offset()?,
+        };
+        // without rules the US rules are the POSIX default
+        let (start, end) = if parser.rest.is_empty() {
+            ((DateRule::Month(3, 2, 0), 7200), (DateRule::Month(11, 1, 0), 7200))
+        } else {
+            parser.rest = parser.rest.strip_prefix(',')?;
+            let start = parser.transition()?;
+            parser.rest = parser.rest.strip_prefix(',')?;
+            (start, parser.transition()?)
+        };
+        parser.rest.is_empty().then_some(Self { std_offset, dst: Some(Dst { offset: dst_offset, start, end }) })
+    }
+
+    fn offset_at(&self, time: i64) -> i32 {
+        let Some(dst) = &self.dst else {
+            return self.std_offset;
+        };
+        let (year, _, _) = civil_from_days((time + self.std_offset as i64).div_euclid(86400));
+        // transition times are given in the local time that is in effect before them
+        let start = dst.start.0.day(year) * 86400 + dst.start.1 as i64 - self.std_offset as i64;
+        let end = dst.end.0.day(year) * 86400 + dst.end.1 as i64 - dst.offset as i64;
+        let in_dst = if start < end { start <= time && time < end } else { !(end <= time && time < start) };
+        if in_dst { dst.offset } else { self.std_offset }
+    }
+}
+
+impl DateRule {
+    /// Days since the epoch of this rule's date in `year`
+    fn day(&self, year: i64) -> i64 {
+        let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+        let jan1 = days_from_civil(year, 1, 1);
+        match *self {
+            DateRule::Julian(day) => jan1 + day as i64 - 1 + i64::from(leap && day > 59),
+            DateRule::Zero(day) => jan1 + day as i64,
+            DateRule::Month(month, week, weekday) => {
+                let first = days_from_civil(year, month as i64, 1);
+                // 1970-01-01 was a Thursday
+                let first_weekday = (first + 4).rem_euclid(7);
+                let mut day = first + (weekday as i64 - first_weekday).rem_euclid(7) + (week as i64 - 1) * 7;
+                let next_month = if month == 12 {
+                    days_from_civil(year + 1, 1, 1)
+                } else {
+                    days_from_civil(year, month as i64 + 1, 1)
+                };
+                while day >= next_month {
+                    day -= 7;
+                }
+                day
+            }
+        }
+    }
+}
+
+struct PosixParser<'a> {
+    rest: &'a str,
+}
+
+impl PosixParser<'_> {
+    /// `EST` or `<+08>`
+    fn name(&mut self) -> Option<()> {
+        let len = match self.rest.strip_prefix('<') {
+            Some(quoted) => quoted.find('>')? + 2,
+            None => self.rest.find(|c: char| !c.is_ascii_alphabetic()).unwrap_or(self.rest.len()),
+        };
+        if len < 3 {
+            return None;
+        }
+        self.rest = &self.rest[len..];
+        Some(())
+    }
+
+    /// `[+-]hh[:mm[:ss]]` in seconds, hours go up to 167 as in the RFC 8536 extension
+    fn offset(&mut self) -> Option<i32> {
+        let sign = match self.rest.chars().next()? {
+            '-' => -1,
+            '+' => 1,
+            _ => 0,
+        };
+        if sign != 0 {
+            self.rest = &self.rest[1..];
+        }
+        let mut seconds: i32 = 0;
+        for (i, (unit, max)) in [(3600, 167), (60, 59), (1, 59)].into_iter().enumerate() {
+            if i > 0 {
+                match self.rest.strip_prefix(':') {
+                    Some(rest) => self.rest = rest,
+                    None => break,
+                }
+            }
+            let len = self.rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(self.rest.len());
+            let value = self.rest[..len].parse::<i32>().ok().filter(|value| *value <= max)?;
+            seconds = value.checked_mul(unit).and_then(|value| seconds.checked_add(value))?;
+            self.rest = &self.rest[len..];
+        }
+        Some(if sign < 0 { -seconds } else { seconds })
+    }
+
+    fn number(&mut self) -> Option<u16> {
+        let len = self.rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(self.rest.len());
+        let number = self.rest[..len].parse().ok()?;
+        self.rest = &self.rest[len..];
+        Some(number)
+    }
+
+    /// `date[/time]`, time defaults to 02:00:00
+    fn transition(&mut self) -> Option<(DateRule, i32)> {
+        let date = if let Some(rest) = self.rest.strip_prefix('J') {
+            self.rest = rest;
+            DateRule::Julian(self.number().filter(|day| (1..=365).contains(day))?)
+        } else if let Some(rest) = self.rest.strip_prefix('M') {
+            self.rest = rest;
+            let month = self.number().filter(|month| (1..=12).contains(month))?;
+            self.rest = self.rest.strip_prefix('.')?;
+            let week = self.number().filter(|week| (1..=5).contains(week))?;
+            self.rest = self.rest.strip_prefix('.')?;
+            let weekday = self.number().filter(|weekday| *weekday <= 6)?;
+            DateRule::Month(month as u8, week as u8, weekday as u8)
+        } else {
+            DateRule::Zero(self.number().filter(|day| *day <= 365)?)
+        };
+        let time = match self.rest.strip_prefix('/') {
+            Some(rest) => {
+                self.rest = rest;
+                self.offset()?
+            }
+            None => 7200,
+        };
+        Some((date, time))
+    }
+}
+
+/// Days since 1970-01-01 of a proleptic Gregorian date
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+/// Inverse of `days_from_civil`
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    (year_of_era + era * 400 + i64::from(month <= 2), month, day)
+}
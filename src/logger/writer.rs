@@ -1,8 +1,26 @@
 use std::{
-    fs::File,
+    fs,
+    fs::{File, OpenOptions},
     io,
-    io::Write,
-    sync::{Arc, Mutex},
+    io::{IsTerminal, Write},
+    path::{Path, PathBuf},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
+
+use log::{Level, Record};
+use utc_dt::{
+    UTCDatetime,
+    time::{UTCTimestamp, UTCTransformations},
+};
+
+use super::{
+    clock::{Clock, SystemClock},
+    formatter::{FormatContext, LogFormatter},
+    retention::{Archiver, RetentionPolicy, with_suffix},
 };
 
 /// LogWriter is used to write log to a specific output, such as stdout, stderr or a file
@@ -10,6 +28,42 @@ pub trait LogWriter: Sync + Send + 'static {
     type Stream: Write;
 
     fn get(&self) -> Self::Stream;
+
+    /// Whether the output is an interactive terminal, which `ColorMode::Auto` colours
+    fn is_terminal(&self) -> bool {
+        false
+    }
+
+    /// Whether the output is a file, which only `ColorMode::Always` colours
+    fn is_file(&self) -> bool {
+        false
+    }
+
+    /// Format a record and write it. Writers that route records, such as [`Tee`](super::tee::Tee), override this
+    /// to pick their own destination and formatter per record
+    fn write_record(&self, record: &Record, ctx: &FormatContext, formatter: &dyn LogFormatter) -> io::Result<()> {
+        // the whole line is written in one call, so writers never see half a record
+        let mut line = Vec::with_capacity(256);
+        formatter.format(&mut line, record, ctx)?;
+        let mut stream = self.get();
+        stream.write_all(&line)?;
+        stream.flush()
+    }
+
+    /// Write a line that is already formatted, such as one queued by an
+    /// [`AsyncWriter`](super::async_writer::AsyncWriter). Writers that route records override this to pick the
+    /// destination from `level`
+    fn write_line(&self, _level: Level, line: &[u8]) -> io::Result<()> {
+        let mut stream = self.get();
+        stream.write_all(line)?;
+        stream.flush()
+    }
+
+    /// Wait until everything written so far has reached the output, for writers that buffer such as
+    /// [`AsyncWriter`](super::async_writer::AsyncWriter)
+    fn sync(&self) -> io::Result<()> {
+        Ok(())
+    }
 }
 
 /// Stdout is used to write log to stdout
@@ -21,6 +75,10 @@ impl LogWriter for Stdout {
     fn get(&self) -> Self::Stream {
         io::stdout().lock()
     }
+
+    fn is_terminal(&self) -> bool {
+        io::stdout().is_terminal()
+    }
 }
 
 /// Stderr is used to write log to stderr
@@ -32,13 +90,181 @@ impl LogWriter for Stderr {
     fn get(&self) -> Self::Stream {
         io::stderr().lock()
     }
+
+    fn is_terminal(&self) -> bool {
+        io::stderr().is_terminal()
+    }
+}
+
+/// SplitWriter sends WARN and ERROR to stderr and everything else to stdout, as container platforms treat stderr as
+/// errors. The threshold and both writers can be changed
+pub struct SplitWriter<L: LogWriter = Stdout, H: LogWriter = Stderr> {
+    low: L,
+    high: H,
+    threshold: Level,
+}
+
+impl SplitWriter {
+    pub fn new() -> Self {
+        Self::with_writers(Stdout, Stderr)
+    }
+}
+
+impl Default for SplitWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<L: LogWriter, H: LogWriter> SplitWriter<L, H> {
+    /// Records at or above the threshold go to `high`, the rest to `low`
+    pub fn with_writers(low: L, high: H) -> Self {
+        Self { low, high, threshold: Level::Warn }
+    }
+
+    /// The least severe level written to the high writer, `Level::Error` keeps warnings on stdout
+    pub fn with_threshold(mut self, threshold: Level) -> Self {
+        self.threshold = threshold;
+        self
+    }
+}
+
+impl<L: LogWriter, H: LogWriter> LogWriter for SplitWriter<L, H> {
+    type Stream = H::Stream;
+
+    /// Raw bytes have no level and go to the high writer
+    fn get(&self) -> Self::Stream {
+        self.high.get()
+    }
+
+    /// Colour only when both writers are terminals
+    fn is_terminal(&self) -> bool {
+        self.low.is_terminal() && self.high.is_terminal()
+    }
+
+    fn is_file(&self) -> bool {
+        self.low.is_file() || self.high.is_file()
+    }
+
+    fn write_record(&self, record: &Record, ctx: &FormatContext, formatter: &dyn LogFormatter) -> io::Result<()> {
+        if record.level() <= self.threshold {
+            self.high.write_record(record, ctx, formatter)
+        } else {
+            self.low.write_record(record, ctx, formatter)
+        }
+    }
+
+    fn write_line(&self, level: Level, line: &[u8]) -> io::Result<()> {
+        if level <= self.threshold { self.high.write_line(level, line) } else { self.low.write_line(level, line) }
+    }
+
+    fn sync(&self) -> io::Result<()> {
+        self.low.sync().and(self.high.sync())
+    }
+}
+
+/// BoxWriter holds any LogWriter behind a pointer, so the writer can be chosen at runtime without naming its type
+pub struct BoxWriter(Box<dyn ErasedWriter>);
+
+impl BoxWriter {
+    pub fn new<W>(writer: W) -> Self
+    where
+        W: LogWriter,
+        W::Stream: 'static,
+    {
+        Self(Box::new(writer))
+    }
+}
+
+impl LogWriter for BoxWriter {
+    type Stream = Box<dyn Write>;
+
+    fn get(&self) -> Self::Stream {
+        self.0.boxed_stream()
+    }
+
+    fn is_terminal(&self) -> bool {
+        self.0.erased_is_terminal()
+    }
+
+    fn is_file(&self) -> bool {
+        self.0.erased_is_file()
+    }
+
+    fn write_record(&self, record: &Record, ctx: &FormatContext, formatter: &dyn LogFormatter) -> io::Result<()> {
+        self.0.erased_write_record(record, ctx, formatter)
+    }
+
+    fn write_line(&self, level: Level, line: &[u8]) -> io::Result<()> {
+        self.0.erased_write_line(level, line)
+    }
+
+    fn sync(&self) -> io::Result<()> {
+        self.0.erased_sync()
+    }
+}
+
+/// Object safe part of LogWriter
+trait ErasedWriter: Send + Sync {
+    fn boxed_stream(&self) -> Box<dyn Write>;
+
+    fn erased_is_terminal(&self) -> bool;
+
+    fn erased_is_file(&self) -> bool;
+
+    fn erased_write_record(&self, record: &Record, ctx: &FormatContext, formatter: &dyn LogFormatter)
+    -> io::Result<()>;
+
+    fn erased_write_line(&self, level: Level, line: &[u8]) -> io::Result<()>;
+
+    fn erased_sync(&self) -> io::Result<()>;
+}
+
+impl<W> ErasedWriter for W
+where
+    W: LogWriter,
+    W::Stream: 'static,
+{
+    fn boxed_stream(&self) -> Box<dyn Write> {
+        Box::new(self.get())
+    }
+
+    fn erased_is_terminal(&self) -> bool {
+        self.is_terminal()
+    }
+
+    fn erased_is_file(&self) -> bool {
+        self.is_file()
+    }
+
+    fn erased_write_record(
+        &self,
+        record: &Record,
+        ctx: &FormatContext,
+        formatter: &dyn LogFormatter,
+    ) -> io::Result<()> {
+        self.write_record(record, ctx, formatter)
+    }
+
+    fn erased_write_line(&self, level: Level, line: &[u8]) -> io::Result<()> {
+        self.write_line(level, line)
+    }
+
+    fn erased_sync(&self) -> io::Result<()> {
+        self.sync()
+    }
 }
 
 /// SharedFile is a thread-safe wrapper around a file that allows multiple threads to write to it concurrently
-#[derive(Clone)]
-pub struct SharedFile(Arc<Mutex<File>>);
+pub struct SharedFile<F = File>(Arc<Mutex<F>>);
 
-impl Write for SharedFile {
+impl<F> Clone for SharedFile<F> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<F: Write> Write for SharedFile<F> {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
         let mut file = self.0.lock().unwrap();
         file.write(buf)
@@ -50,6 +276,48 @@ impl Write for SharedFile {
     }
 }
 
+/// FileOptions controls how log files are created when opened by path
+#[derive(Clone, Debug)]
+pub struct FileOptions {
+    #[cfg(unix)]
+    mode: u32,
+}
+
+impl Default for FileOptions {
+    fn default() -> Self {
+        Self {
+            #[cfg(unix)]
+            mode: 0o644,
+        }
+    }
+}
+
+impl FileOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Unix permissions of a newly created file, before the process umask is applied. Defaults to `0o644`
+    #[cfg(unix)]
+    pub fn mode(mut self, mode: u32) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Files are opened with O_APPEND, so every write lands at the end of the file even when several
+    /// processes log to it at the same time
+    fn open(&self, path: &Path) -> io::Result<File> {
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+        let mut options = OpenOptions::new();
+        options.create(true).append(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, self.mode);
+        options.open(path)
+    }
+}
+
 /// LogFileWriter is a simple implementation of LogWriter that writes logs to a single file.
 pub struct LogFileWriter {
     file: SharedFile,
@@ -59,6 +327,11 @@ impl LogFileWriter {
     pub fn new(file: File) -> Self {
         Self { file: SharedFile(Arc::new(Mutex::new(file))) }
     }
+
+    /// Open `path` in append mode, creating the file and its parent directories if they don't exist
+    pub fn open(path: impl AsRef<Path>, options: &FileOptions) -> io::Result<Self> {
+        Ok(Self::new(options.open(path.as_ref())?))
+    }
 }
 
 impl LogWriter for LogFileWriter {
@@ -67,4 +340,579 @@ impl LogWriter for LogFileWriter {
     fn get(&self) -> Self::Stream {
         self.file.clone()
     }
+
+    fn is_file(&self) -> bool {
+        true
+    }
+}
+
+/// RotatingFile is a file that rolls over to `path.1`, `path.2` ... once writing to it would exceed `max_bytes`.
+/// Every `write` call lands entirely in one file, so a log line is never split across two files
+pub struct RotatingFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    max_bytes: u64,
+    max_backups: usize,
+    archiver: Option<Archiver>,
+    options: FileOptions,
+    /// The last rotation failed and was reported, it is retried on every write until it succeeds
+    failing: bool,
+}
+
+impl RotatingFile {
+    fn backup_path(&self, index: usize) -> PathBuf {
+        with_suffix(&self.path, format!(".{index}"))
+    }
+
+    /// Renames backup `from` to `to`, whether or not the retention policy has compressed it
+    fn shift_backup(&self, from: usize, to: usize) -> io::Result<()> {
+        for suffix in ["", ".gz"] {
+            let path = with_suffix(&self.backup_path(from), suffix);
+            if path.exists() {
+                fs::rename(path, with_suffix(&self.backup_path(to), suffix))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        if self.max_backups == 0 {
+            // the file is in append mode, so writes continue at the new end
+            self.file.set_len(0)?;
+            self.size = 0;
+            return Ok(());
+        }
+
+        if let Some(archiver) = &mut self.archiver {
+            archiver.wait();
+        }
+        // app.log.N is dropped, app.log.N-1 becomes app.log.N ... app.log becomes app.log.1. Only the backups
+        // before the first free slot move, so a retry after a failed rename continues where it stopped
+        let exists = |index| {
+            let path = self.backup_path(index);
+            path.exists() || with_suffix(&path, ".gz").exists()
+        };
+        let free = (1..=self.max_backups).find(|index| !exists(*index)).unwrap_or(self.max_backups);
+        let _ = fs::remove_file(self.backup_path(free));
+        let _ = fs::remove_file(with_suffix(&self.backup_path(free), ".gz"));
+        for index in (1..free).rev() {
+            self.shift_backup(index, index + 1)?;
+        }
+        fs::rename(&self.path, self.backup_path(1))?;
+        self.file = self.options.open(&self.path)?;
+        self.size = 0;
+
+        if self.archiver.is_some() {
+            let archived = (1..=self.max_backups)
+                .map(|index| self.backup_path(index))
+                .flat_map(|path| [with_suffix(&path, ".gz"), path])
+                .filter(|path| path.exists())
+                .collect();
+            let closed = self.backup_path(1);
+            if let Some(archiver) = &mut self.archiver {
+                archiver.archive(closed, archived);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.size > 0 && self.size + buf.len() as u64 > self.max_bytes {
+            // a failed rotation keeps the record in the current file rather than losing it
+            match self.rotate() {
+                Ok(()) => self.failing = false,
+                Err(err) if !self.failing => {
+                    self.failing = true;
+                    crate::log_print!(Level::Warn, "failed to rotate {}: {}", self.path.display(), err);
+                }
+                Err(_) => {}
+            }
+        }
+        self.file.write_all(buf)?;
+        self.size += buf.len() as u64;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// RotatingFileWriter writes logs to `path` and rolls over to `path.1`, `path.2` ... once the file exceeds
+/// `max_bytes`, keeping at most `max_backups` old files
+pub struct RotatingFileWriter {
+    file: SharedFile<RotatingFile>,
+}
+
+impl RotatingFileWriter {
+    pub fn new(path: impl AsRef<Path>, max_bytes: u64, max_backups: usize) -> io::Result<Self> {
+        Self::open(path, max_bytes, max_backups, &FileOptions::default())
+    }
+
+    /// Like `new`, creating the file and its directory with `options`
+    pub fn open(path: impl AsRef<Path>, max_bytes: u64, max_backups: usize, options: &FileOptions) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = options.open(&path)?;
+        let size = file.metadata()?.len();
+        let options = options.clone();
+        let file = RotatingFile { path, file, size, max_bytes, max_backups, archiver: None, options, failing: false };
+        Ok(Self { file: SharedFile(Arc::new(Mutex::new(file))) })
+    }
+
+    /// Hand every rotated file to `policy`, see [`Retention`](crate::Retention)
+    pub fn with_retention(self, policy: impl RetentionPolicy) -> Self {
+        self.file.0.lock().unwrap().archiver = Some(Archiver::new(policy));
+        self
+    }
+}
+
+impl LogWriter for RotatingFileWriter {
+    type Stream = SharedFile<RotatingFile>;
+
+    fn get(&self) -> Self::Stream {
+        self.file.clone()
+    }
+
+    fn is_file(&self) -> bool {
+        true
+    }
+}
+
+/// Rotation is the interval at which TimeRotatingFileWriter starts a new file, aligned to UTC
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rotation {
+    Daily,
+    Hourly,
+}
+
+impl Rotation {
+    fn seconds(&self) -> u64 {
+        match self {
+            Rotation::Daily => 86400,
+            Rotation::Hourly => 3600,
+        }
+    }
+
+    fn period(&self, now: Duration) -> u64 {
+        now.as_secs() / self.seconds()
+    }
+
+    /// `2026-10-17` for daily rotation, `2026-10-17-13` for hourly rotation
+    fn suffix(&self, period: u64) -> String {
+        let datetime = UTCDatetime::from_timestamp(UTCTimestamp::from_secs(period * self.seconds()));
+        let iso = datetime.as_iso_datetime(0);
+        match self {
+            Rotation::Daily => iso[..10].to_string(),
+            Rotation::Hourly => format!("{}-{}", &iso[..10], &iso[11..13]),
+        }
+    }
+
+    /// Whether `suffix` has the shape of [`Rotation::suffix`], such as `2026-10-17` for daily rotation
+    fn is_suffix(&self, suffix: &str) -> bool {
+        let shape = match self {
+            Rotation::Daily => "dddd-dd-dd",
+            Rotation::Hourly => "dddd-dd-dd-dd",
+        };
+        suffix.len() == shape.len()
+            && suffix.bytes().zip(shape.bytes()).all(|(c, s)| if s == b'd' { c.is_ascii_digit() } else { c == s })
+    }
+}
+
+/// TimeRotatingFile is a file that switches to a new dated file on the first write after a UTC day or hour boundary
+pub struct TimeRotatingFile {
+    path: PathBuf,
+    rotation: Rotation,
+    period: u64,
+    file: File,
+    archiver: Option<Archiver>,
+    options: FileOptions,
+    clock: Box<dyn Clock>,
+    /// The last rollover failed and was reported, it is retried on every write until it succeeds
+    failing: bool,
+}
+
+impl TimeRotatingFile {
+    /// `app.log` becomes `app.2026-10-17.log`, `app` becomes `app.2026-10-17`
+    fn period_path(path: &Path, rotation: Rotation, period: u64) -> PathBuf {
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+        let name = match path.extension() {
+            Some(ext) => format!("{stem}.{}.{}", rotation.suffix(period), ext.to_string_lossy()),
+            None => format!("{stem}.{}", rotation.suffix(period)),
+        };
+        path.with_file_name(name)
+    }
+
+    fn open(&self, period: u64) -> io::Result<File> {
+        self.options.open(&Self::period_path(&self.path, self.rotation, period))
+    }
+
+    fn roll_over(&mut self, period: u64) -> io::Result<()> {
+        self.file.flush()?;
+        self.file = self.open(period)?;
+        let closed = Self::period_path(&self.path, self.rotation, self.period);
+        self.period = period;
+
+        if self.archiver.is_some() {
+            let archived = self.archived();
+            if let Some(archiver) = &mut self.archiver {
+                archiver.archive(closed, archived);
+            }
+        }
+        Ok(())
+    }
+
+    /// Every dated file of this writer except the current one, newest first
+    fn archived(&self) -> Vec<PathBuf> {
+        let dir = match self.path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+        let prefix = format!("{}.", self.path.file_stem().unwrap_or_default().to_string_lossy());
+        let extension = self.path.extension().map(|ext| format!(".{}", ext.to_string_lossy())).unwrap_or_default();
+        let current = Self::period_path(&self.path, self.rotation, self.period);
+
+        let mut archived: Vec<PathBuf> = fs::read_dir(dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| {
+                let name = path.file_name().unwrap_or_default().to_string_lossy();
+                let name = name.strip_suffix(".gz").unwrap_or(&name);
+                // only this writer's dated files, not `app.error.log` next to `app.log`
+                let suffix = name.strip_prefix(&prefix).and_then(|name| name.strip_suffix(&extension));
+                suffix.is_some_and(|suffix| self.rotation.is_suffix(suffix)) && *path != current
+            })
+            .collect();
+        archived.sort_by(|a, b| b.cmp(a));
+        archived
+    }
+}
+
+impl Write for TimeRotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let period = self.rotation.period(self.clock.now());
+        if period != self.period {
+            // a failed rollover keeps the record in the current file rather than losing it
+            match self.roll_over(period) {
+                Ok(()) => self.failing = false,
+                Err(err) if !self.failing => {
+                    self.failing = true;
+                    crate::log_print!(Level::Warn, "failed to rotate {}: {}", self.path.display(), err);
+                }
+                Err(_) => {}
+            }
+        }
+        self.file.write_all(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// TimeRotatingFileWriter writes logs to dated files derived from `path`, such as `app.2026-10-17.log`,
+/// starting a new file on the first record after each UTC day or hour boundary
+pub struct TimeRotatingFileWriter {
+    file: SharedFile<TimeRotatingFile>,
+}
+
+impl TimeRotatingFileWriter {
+    pub fn new(path: impl AsRef<Path>, rotation: Rotation) -> io::Result<Self> {
+        Self::open(path, rotation, &FileOptions::default())
+    }
+
+    /// Like `new`, creating the files and their directory with `options`
+    pub fn open(path: impl AsRef<Path>, rotation: Rotation, options: &FileOptions) -> io::Result<Self> {
+        Self::open_with_clock(path, rotation, options, SystemClock)
+    }
+
+    /// Like `open`, rotating by the time read from `clock` instead of the system clock, see [`Clock`]
+    pub fn open_with_clock(
+        path: impl AsRef<Path>,
+        rotation: Rotation,
+        options: &FileOptions,
+        clock: impl Clock,
+    ) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let period = rotation.period(clock.now());
+        let file = options.open(&TimeRotatingFile::period_path(&path, rotation, period))?;
+        let options = options.clone();
+        let clock = Box::new(clock);
+        let file = TimeRotatingFile { path, rotation, period, file, archiver: None, options, clock, failing: false };
+        Ok(Self { file: SharedFile(Arc::new(Mutex::new(file))) })
+    }
+
+    /// Hand every rotated file to `policy`, see [`Retention`](crate::Retention)
+    pub fn with_retention(self, policy: impl RetentionPolicy) -> Self {
+        self.file.0.lock().unwrap().archiver = Some(Archiver::new(policy));
+        self
+    }
+}
+
+impl LogWriter for TimeRotatingFileWriter {
+    type Stream = SharedFile<TimeRotatingFile>;
+
+    fn get(&self) -> Self::Stream {
+        self.file.clone()
+    }
+
+    fn is_file(&self) -> bool {
+        true
+    }
+}
+
+/// ReopenableFile is a file that can be reopened by path, so logs follow the path after an external tool
+/// such as logrotate has moved the file away
+pub struct ReopenableFile {
+    path: PathBuf,
+    file: File,
+    options: FileOptions,
+    /// Set from a signal handler, the reopen happens on the next write
+    pending: Arc<AtomicBool>,
+    /// The last reopen after a signal failed and was reported, it is retried on every write until it succeeds
+    failing: bool,
+}
+
+impl ReopenableFile {
+    fn reopen(&mut self) -> io::Result<()> {
+        // open the new file before touching the old one, so a failed reopen keeps logging to the old file
+        let file = self.options.open(&self.path)?;
+        self.file.flush()?;
+        self.file = file;
+        Ok(())
+    }
+}
+
+impl Write for ReopenableFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.pending.swap(false, Ordering::Relaxed) {
+            // a failed reopen keeps the record in the old file rather than losing it
+            match self.reopen() {
+                Ok(()) => self.failing = false,
+                Err(err) => {
+                    self.pending.store(true, Ordering::Relaxed);
+                    if !self.failing {
+                        self.failing = true;
+                        crate::log_print!(Level::Warn, "failed to reopen {}: {}", self.path.display(), err);
+                    }
+                }
+            }
+        }
+        self.file.write_all(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// ReopenHandle reopens the path of a ReopenableFileWriter, it can be cloned and moved to other threads
+#[derive(Clone)]
+pub struct ReopenHandle {
+    file: SharedFile<ReopenableFile>,
+}
+
+impl ReopenHandle {
+    /// Reopen the file at the writer's path. The swap happens under the file lock, so every record is
+    /// written completely to either the old or the new file
+    pub fn reopen(&self) -> io::Result<()> {
+        self.file.0.lock().unwrap().reopen()
+    }
+}
+
+/// ReopenableFileWriter writes logs to `path` and reopens it on request, which works with logrotate's
+/// default move-and-create mode
+pub struct ReopenableFileWriter {
+    file: SharedFile<ReopenableFile>,
+}
+
+impl ReopenableFileWriter {
+    pub fn new(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::open(path, &FileOptions::default())
+    }
+
+    /// Like `new`, creating the file and its directory with `options`
+    pub fn open(path: impl AsRef<Path>, options: &FileOptions) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = options.open(&path)?;
+        let options = options.clone();
+        let file = ReopenableFile { path, file, options, pending: Arc::new(AtomicBool::new(false)), failing: false };
+        Ok(Self { file: SharedFile(Arc::new(Mutex::new(file))) })
+    }
+
+    pub fn handle(&self) -> ReopenHandle {
+        ReopenHandle { file: self.file.clone() }
+    }
+
+    /// Reopen the file on the first write after the process receives SIGHUP
+    #[cfg(all(unix, feature = "signal"))]
+    pub fn reopen_on_sighup(self) -> io::Result<Self> {
+        let pending = self.file.0.lock().unwrap().pending.clone();
+        signal_hook::flag::register(signal_hook::consts::SIGHUP, pending)?;
+        Ok(self)
+    }
+}
+
+impl LogWriter for ReopenableFileWriter {
+    type Stream = SharedFile<ReopenableFile>;
+
+    fn get(&self) -> Self::Stream {
+        self.file.clone()
+    }
+
+    fn is_file(&self) -> bool {
+        true
+    }
+}
+
+/// Target is a writer picked at runtime, for outputs that come from configuration rather than code
+pub enum Target {
+    Stdout,
+    Stderr,
+    File(LogFileWriter),
+    RotatingFile(RotatingFileWriter),
+    TimeRotatingFile(TimeRotatingFileWriter),
+    ReopenableFile(ReopenableFileWriter),
+}
+
+/// Stream of a [`Target`]
+pub enum TargetStream {
+    Stdout(io::StdoutLock<'static>),
+    Stderr(io::StderrLock<'static>),
+    File(SharedFile),
+    RotatingFile(SharedFile<RotatingFile>),
+    TimeRotatingFile(SharedFile<TimeRotatingFile>),
+    ReopenableFile(SharedFile<ReopenableFile>),
+}
+
+impl TargetStream {
+    fn inner(&mut self) -> &mut dyn Write {
+        match self {
+            TargetStream::Stdout(stream) => stream,
+            TargetStream::Stderr(stream) => stream,
+            TargetStream::File(stream) => stream,
+            TargetStream::RotatingFile(stream) => stream,
+            TargetStream::TimeRotatingFile(stream) => stream,
+            TargetStream::ReopenableFile(stream) => stream,
+        }
+    }
+}
+
+impl Write for TargetStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.inner().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner().flush()
+    }
+}
+
+impl LogWriter for Target {
+    type Stream = TargetStream;
+
+    fn get(&self) -> Self::Stream {
+        match self {
+            Target::Stdout => TargetStream::Stdout(Stdout.get()),
+            Target::Stderr => TargetStream::Stderr(Stderr.get()),
+            Target::File(file) => TargetStream::File(file.get()),
+            Target::RotatingFile(file) => TargetStream::RotatingFile(file.get()),
+            Target::TimeRotatingFile(file) => TargetStream::TimeRotatingFile(file.get()),
+            Target::ReopenableFile(file) => TargetStream::ReopenableFile(file.get()),
+        }
+    }
+
+    fn is_terminal(&self) -> bool {
+        match self {
+            Target::Stdout => Stdout.is_terminal(),
+            Target::Stderr => Stderr.is_terminal(),
+            _ => false,
+        }
+    }
+
+    fn is_file(&self) -> bool {
+        !matches!(self, Target::Stdout | Target::Stderr)
+    }
+}
+
+/// A list of writers gets every line, for example a file and stderr at the same time
+impl<W: LogWriter> LogWriter for Vec<W> {
+    type Stream = Fanout<W::Stream>;
+
+    fn get(&self) -> Self::Stream {
+        Fanout(self.iter().map(LogWriter::get).collect())
+    }
+
+    /// Colour only when every writer is a terminal
+    fn is_terminal(&self) -> bool {
+        !self.is_empty() && self.iter().all(LogWriter::is_terminal)
+    }
+
+    /// Any file in the list keeps `ColorMode::Auto` from colouring, as every writer gets the same line
+    fn is_file(&self) -> bool {
+        self.iter().any(LogWriter::is_file)
+    }
+
+    /// Each writer writes the record itself, so a [`Tee`](super::tee::Tee) in the list still routes by level
+    fn write_record(&self, record: &Record, ctx: &FormatContext, formatter: &dyn LogFormatter) -> io::Result<()> {
+        let mut result = Ok(());
+        for writer in self {
+            if let Err(err) = writer.write_record(record, ctx, formatter) {
+                result = result.and(Err(err));
+            }
+        }
+        result
+    }
+
+    fn write_line(&self, level: Level, line: &[u8]) -> io::Result<()> {
+        let mut result = Ok(());
+        for writer in self {
+            if let Err(err) = writer.write_line(level, line) {
+                result = result.and(Err(err));
+            }
+        }
+        result
+    }
+
+    fn sync(&self) -> io::Result<()> {
+        let mut result = Ok(());
+        for writer in self {
+            if let Err(err) = writer.sync() {
+                result = result.and(Err(err));
+            }
+        }
+        result
+    }
+}
+
+/// Fanout writes everything to all of its streams, a failing stream doesn't stop the others
+pub struct Fanout<S>(pub(crate) Vec<S>);
+
+impl<S: Write> Write for Fanout<S> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut result = Ok(buf.len());
+        for stream in &mut self.0 {
+            if let Err(err) = stream.write_all(buf) {
+                result = Err(err);
+            }
+        }
+        result
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let mut result = Ok(());
+        for stream in &mut self.0 {
+            if let Err(err) = stream.flush() {
+                result = Err(err);
+            }
+        }
+        result
+    }
 }